@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+
+// cargo run --bin deployboard-proxy -- deployboard-proxy.json
+// the web build of deployboard sets `proxy: Some("http://127.0.0.1:8787")` and sends everything to `/{target}/...`
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct Config {
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    /// Origin the web build is served from, e.g. http://127.0.0.1:8080 for trunk serve.
+    /// Requests from any other origin are rejected, the proxy adds credentials to everything it forwards.
+    pub allowed_origin: String,
+    /// `Host` headers accepted besides `listen` and its localhost form, against DNS rebinding.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    pub targets: HashMap<String, Target>,
+}
+
+impl Config {
+    fn host_allowed(&self, host: &str) -> bool {
+        let localhost = self
+            .listen
+            .strip_prefix("127.0.0.1:")
+            .map(|port| format!("localhost:{}", port));
+        host == self.listen || Some(host) == localhost.as_deref() || self.allowed_hosts.iter().any(|x| x == host)
+    }
+}
+
+/// Larger bodies are answered with 413 before anything is allocated for them.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+/// Longest request or header line.
+const MAX_LINE: u64 = 16 * 1024;
+
+fn default_listen() -> String {
+    "127.0.0.1:8787".to_string()
+}
+
+/// Header values of the form `env:NAME` are read from the environment, so tokens stay out of the file.
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct Target {
+    pub endpoint: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl Target {
+    fn headers(&self) -> Result<Vec<(String, String)>, String> {
+        self.headers
+            .iter()
+            .map(|(name, value)| match value.strip_prefix("env:") {
+                Some(var) => std::env::var(var)
+                    .map(|value| (name.to_lowercase(), value))
+                    .map_err(|_| format!("environment variable {} is not set", var)),
+                None => Ok((name.to_lowercase(), value.clone())),
+            })
+            .collect()
+    }
+}
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    fn error(status: u16, status_text: &str, message: String) -> Self {
+        Self {
+            status,
+            status_text: status_text.to_string(),
+            headers: vec![("content-type".to_string(), "text/plain".to_string())],
+            body: message.into_bytes(),
+        }
+    }
+}
+
+/// Response headers the browser is allowed to read, gitlab paginates with x-next-page.
+const EXPOSED_HEADERS: [&str; 4] = ["content-type", "x-next-page", "x-total", "x-total-pages"];
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "deployboard-proxy.json".to_string());
+    let config = match std::fs::read_to_string(&path)
+        .map_err(|e| format!("{}: {}", path, e))
+        .and_then(|text| serde_json::from_str::<Config>(&text).map_err(|e| format!("{}: {}", path, e)))
+    {
+        Ok(config) => std::sync::Arc::new(config),
+        Err(err) => {
+            eprintln!("err :{}", err);
+            std::process::exit(1);
+        }
+    };
+    for (name, target) in config.targets.iter() {
+        if let Err(err) = target.headers() {
+            eprintln!("err :{}: {}", name, err);
+            std::process::exit(1);
+        }
+    }
+
+    let listener = match std::net::TcpListener::bind(&config.listen) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("err :{}: {}", config.listen, err);
+            std::process::exit(1);
+        }
+    };
+    println!("listening on http://{}", config.listen);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let config = config.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = handle(&config, stream) {
+                println!("err :{}", err);
+            }
+        });
+    }
+}
+
+fn handle(config: &Config, mut stream: std::net::TcpStream) -> Result<(), String> {
+    let response = match read_request(&mut stream) {
+        Ok(request) => {
+            let response = forward(config, &request);
+            println!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+    write_response(config, &mut stream, &response)
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, Response> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE)
+        .read_line(&mut line)
+        .map_err(|e| Response::error(400, "Bad Request", e.to_string()))?;
+    if !line.ends_with('\n') {
+        return Err(Response::error(400, "Bad Request", "incomplete or too long line".to_string()));
+    }
+    Ok(line)
+}
+
+fn read_request(stream: impl Read) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let line = read_line(&mut reader)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "Bad Request", format!("invalid request line {:?}", line)));
+    };
+    let method = method.to_string();
+    let path = path.to_string();
+
+    let mut headers = vec![];
+    loop {
+        let line = read_line(&mut reader)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        return Err(Response::error(
+            413,
+            "Payload Too Large",
+            format!("body of {} bytes is larger than {}", length, MAX_BODY),
+        ));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| Response::error(400, "Bad Request", e.to_string()))?;
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(x, _)| x == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Only the web build may use the credentials, a foreign `Host` means a rebound DNS name pointing at us.
+fn check(config: &Config, request: &Request) -> Result<(), Response> {
+    let host = request.header("host").unwrap_or_default();
+    if !config.host_allowed(host) {
+        return Err(Response::error(403, "Forbidden", format!("host {:?} is not allowed", host)));
+    }
+    let origin = request.header("origin").unwrap_or_default();
+    if origin != config.allowed_origin {
+        return Err(Response::error(403, "Forbidden", format!("origin {:?} is not allowed", origin)));
+    }
+    Ok(())
+}
+
+/// `/{target}/rest` is sent to `{endpoint}/rest`, configured headers replace the ones sent by the browser.
+fn forward(config: &Config, request: &Request) -> Response {
+    if let Err(response) = check(config, request) {
+        return response;
+    }
+    if request.method == "OPTIONS" {
+        return Response {
+            status: 204,
+            status_text: "No Content".to_string(),
+            headers: vec![],
+            body: vec![],
+        };
+    }
+    let path = request.path.trim_start_matches('/');
+    let (name, rest) = path.split_once('/').unwrap_or((path, ""));
+    let Some(target) = config.targets.get(name) else {
+        return Response::error(404, "Not Found", format!("unknown target {}", name));
+    };
+    let target_headers = match target.headers() {
+        Ok(headers) => headers,
+        Err(err) => return Response::error(500, "Internal Server Error", err),
+    };
+
+    let mut forwarded = ehttp::Request {
+        method: request.method.clone(),
+        url: format!("{}/{}", target.endpoint.trim_end_matches('/'), rest),
+        body: request.body.clone(),
+        headers: ehttp::Headers::new(&[]),
+    };
+    for (name, value) in request.headers.iter() {
+        let skip = ["host", "origin", "referer", "connection", "content-length"].contains(&name.as_str())
+            || target_headers.iter().any(|(x, _)| x == name);
+        if !skip {
+            forwarded.headers.insert(name, value);
+        }
+    }
+    for (name, value) in target_headers.iter() {
+        forwarded.headers.insert(name, value);
+    }
+
+    match ehttp::fetch_blocking(&forwarded) {
+        Ok(response) => Response {
+            status: response.status,
+            status_text: response.status_text,
+            headers: response
+                .headers
+                .headers
+                .into_iter()
+                .filter(|(name, _)| EXPOSED_HEADERS.contains(&name.to_lowercase().as_str()))
+                .collect(),
+            body: response.bytes,
+        },
+        Err(err) => Response::error(502, "Bad Gateway", err),
+    }
+}
+
+fn write_response(
+    config: &Config,
+    stream: &mut std::net::TcpStream,
+    response: &Response,
+) -> Result<(), String> {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, response.status_text);
+    for (name, value) in response.headers.iter() {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("access-control-allow-origin: {}\r\n", config.allowed_origin));
+    head.push_str("access-control-allow-credentials: true\r\n");
+    head.push_str("access-control-allow-methods: GET, POST, PUT, DELETE, OPTIONS\r\n");
+    head.push_str("access-control-allow-headers: authorization, content-type, private-token, x-vault-token\r\n");
+    head.push_str(&format!("access-control-expose-headers: {}\r\n", EXPOSED_HEADERS.join(", ")));
+    head.push_str("vary: origin\r\n");
+    head.push_str(&format!("content-length: {}\r\n", response.body.len()));
+    head.push_str("connection: close\r\n\r\n");
+
+    stream.write_all(head.as_bytes()).map_err(|e| e.to_string())?;
+    stream.write_all(&response.body).map_err(|e| e.to_string())?;
+    stream.flush().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    fn config() -> super::Config {
+        serde_json::from_str(
+            r#"{
+                "allowed_origin": "http://127.0.0.1:8080",
+                "allowed_hosts": ["board.internal:8787"],
+                "targets": { "gitlab": { "endpoint": "https://gitlab.test" } }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn request(host: &str, origin: Option<&str>, method: &str) -> super::Request {
+        let mut headers = vec![("host".to_string(), host.to_string())];
+        if let Some(origin) = origin {
+            headers.push(("origin".to_string(), origin.to_string()));
+        }
+        super::Request {
+            method: method.to_string(),
+            path: "/unknown/api".to_string(),
+            headers,
+            body: vec![],
+        }
+    }
+
+    #[test]
+    fn read_request_with_body() {
+        let raw = b"POST /gitlab/api/v4/projects HTTP/1.1\r\nHost: 127.0.0.1:8787\r\nContent-Length: 2\r\n\r\n{}";
+        let request = super::read_request(&raw[..]).ok().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/gitlab/api/v4/projects");
+        assert_eq!(request.header("host"), Some("127.0.0.1:8787"));
+        assert_eq!(request.body, b"{}");
+    }
+
+    #[test]
+    fn read_request_rejects_large_body() {
+        let raw = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", super::MAX_BODY + 1);
+        let response = super::read_request(raw.as_bytes()).err().unwrap();
+        assert_eq!(response.status, 413);
+    }
+
+    #[test]
+    fn read_request_rejects_long_lines() {
+        let raw = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(super::MAX_LINE as usize));
+        let response = super::read_request(raw.as_bytes()).err().unwrap();
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn rejects_other_origins() {
+        let config = config();
+        for origin in [None, Some("http://evil.test"), Some("http://127.0.0.1:8081")] {
+            let response = super::forward(&config, &request("127.0.0.1:8787", origin, "GET"));
+            assert_eq!(response.status, 403);
+        }
+        let response = super::forward(&config, &request("127.0.0.1:8787", Some("http://evil.test"), "OPTIONS"));
+        assert_eq!(response.status, 403);
+    }
+
+    #[test]
+    fn rejects_other_hosts() {
+        let config = config();
+        for host in ["evil.test:8787", "127.0.0.1:8788", ""] {
+            let response = super::forward(&config, &request(host, Some("http://127.0.0.1:8080"), "GET"));
+            assert_eq!(response.status, 403);
+        }
+    }
+
+    #[test]
+    fn allowed_requests_pass_the_checks() {
+        let config = config();
+        for host in ["127.0.0.1:8787", "localhost:8787", "board.internal:8787"] {
+            let response = super::forward(&config, &request(host, Some("http://127.0.0.1:8080"), "OPTIONS"));
+            assert_eq!(response.status, 204);
+            // unknown target, answered without any request upstream
+            let response = super::forward(&config, &request(host, Some("http://127.0.0.1:8080"), "GET"));
+            assert_eq!(response.status, 404);
+        }
+    }
+}
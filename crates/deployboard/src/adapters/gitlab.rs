@@ -42,36 +42,88 @@ pub async  fn get_filecontent(
     Ok(text)
 }
 
-pub fn update_file(
+/// Content and the commit that last touched the file, `last_commit_id` lets a later commit detect changes in between.
+pub async fn get_file(
     config: &ConnectionConfig,
     project_id: &str,
     file_path: &str,
-    update: &FileUpdate,
+    branch: &str,
+) -> Result<File, String> {
+    use base64::Engine;
+    let mut request = ehttp::Request::get(format!(
+        "{}/api/v4/projects/{}/repository/files/{}?ref={}",
+        config.endpoint,
+        project_id,
+        urlencoding::encode(file_path),
+        branch
+    ));
+    request.headers.insert("PRIVATE-TOKEN", &config.token);
+
+    let response = super::http::fetch(&request, false).await?;
+    if !response.ok {
+        return Err(format!("{} could not be read: {}", file_path, response.status_text));
+    }
+    let file = serde_json::from_slice::<RepositoryFile>(&response.bytes).map_err(|e| e.to_string())?;
+    let content = base64::engine::general_purpose::STANDARD
+        .decode(file.content.replace('\n', ""))
+        .map_err(|e| e.to_string())?;
+    Ok(File {
+        content: String::from_utf8(content).map_err(|e| e.to_string())?,
+        last_commit_id: file.last_commit_id,
+    })
+}
+
+/// One commit through the commits api, `actions` may touch several files at once.
+pub async fn create_commit(
+    config: &ConnectionConfig,
+    project_id: &str,
+    commit: &NewCommit,
 ) -> Result<(), String> {
-    let body = serde_json::to_vec(update).map_err(|e| e.to_string())?;
+    let body = serde_json::to_vec(commit).map_err(|e| e.to_string())?;
     let mut request = ehttp::Request::post(
-        format!(
-            "{}/api/v4/projects/{}/repository/files/{}",
-            config.endpoint,
-            project_id,
-            urlencoding::encode(file_path)
-        ),
+        format!("{}/api/v4/projects/{}/repository/commits", config.endpoint, project_id),
         body,
     );
-    request.method = "PUT".to_owned();
     request.headers.insert("PRIVATE-TOKEN", &config.token);
     request.headers.insert("Content-Type", "application/json");
 
-    let response = super::http::fetch_blocking(&request)?;
+    let response = super::http::fetch(&request, false).await?;
     if !response.ok {
+        // a `last_commit_id` that is no longer the latest is answered with 400 and a message saying so
+        let message = serde_json::from_slice::<ErrorResponse>(&response.bytes)
+            .map(|x| x.message.to_string())
+            .unwrap_or_default();
         return Err(format!(
-            "Update file not succeed,\n response was not ok: {}",
-            response.status_text
+            "Commit not succeed,\n response was not ok: {} {}",
+            response.status_text, message
         ));
     }
     Ok(())
 }
 
+pub async fn create_merge_request(
+    config: &ConnectionConfig,
+    project_id: &str,
+    merge_request: &NewMergeRequest,
+) -> Result<MergeRequest, String> {
+    let body = serde_json::to_vec(merge_request).map_err(|e| e.to_string())?;
+    let mut request = ehttp::Request::post(
+        format!("{}/api/v4/projects/{}/merge_requests", config.endpoint, project_id),
+        body,
+    );
+    request.headers.insert("PRIVATE-TOKEN", &config.token);
+    request.headers.insert("Content-Type", "application/json");
+
+    let response = super::http::fetch(&request, false).await?;
+    if !response.ok {
+        return Err(format!(
+            "Create merge request not succeed,\n response was not ok: {}",
+            response.status_text
+        ));
+    }
+    serde_json::from_slice::<MergeRequest>(&response.bytes).map_err(|e| e.to_string())
+}
+
 pub async fn get_filepaths(
     config: &ConnectionConfig,
     project_id: &str,
@@ -136,6 +188,102 @@ pub async fn get_project(config: &ConnectionConfig, project_id: &str) -> Result<
     Ok(project)
 }
 
+pub async fn compare(
+    config: &ConnectionConfig,
+    project_id: &str,
+    from: &str,
+    to: &str,
+) -> Result<Compare, String> {
+    let mut request = ehttp::Request::get(format!(
+        "{}/api/v4/projects/{}/repository/compare?from={}&to={}",
+        config.endpoint, project_id, from, to
+    ));
+    request.headers.insert("PRIVATE-TOKEN", &config.token);
+
+    let response = super::http::fetch(&request, false).await?;
+    if !response.ok {
+        return Err(format!("compare {}..{} failed: {}", from, to, response.status_text));
+    }
+    let compare = serde_json::from_slice::<Compare>(&response.bytes).map_err(|e| e.to_string())?;
+
+    Ok(compare)
+}
+
+pub async fn get_pipelines(
+    config: &ConnectionConfig,
+    project_id: &str,
+    query: &PipelineQuery<'_>,
+) -> Result<Vec<Pipeline>, String> {
+    let mut url = format!(
+        "{}/api/v4/projects/{}/pipelines?per_page={}",
+        config.endpoint, project_id, query.per_page
+    );
+    if let Some(r#ref) = query.r#ref {
+        url.push_str(&format!("&ref={}", urlencoding::encode(r#ref)));
+    }
+    if let Some(sha) = query.sha {
+        url.push_str(&format!("&sha={}", sha));
+    }
+    let mut request = ehttp::Request::get(url);
+    request.headers.insert("PRIVATE-TOKEN", &config.token);
+
+    let response = super::http::fetch(&request, false).await?;
+    if !response.ok {
+        return Err(format!("pipelines of {} failed: {}", project_id, response.status_text));
+    }
+    let pipelines =
+        serde_json::from_slice::<Vec<Pipeline>>(&response.bytes).map_err(|e| e.to_string())?;
+
+    Ok(pipelines)
+}
+
+pub async fn get_latest_pipeline(
+    config: &ConnectionConfig,
+    project_id: &str,
+    query: &PipelineQuery<'_>,
+) -> Result<Option<Pipeline>, String> {
+    get_pipelines(config, project_id, query)
+        .await
+        .map(|pipelines| pipelines.into_iter().next())
+}
+
+#[derive(Debug, Clone)]
+pub struct PipelineQuery<'a> {
+    pub r#ref: Option<&'a str>,
+    pub sha: Option<&'a str>,
+    pub per_page: usize,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Pipeline {
+    pub id: u64,
+    pub status: String,
+    pub r#ref: String,
+    pub sha: String,
+    pub web_url: String,
+}
+
+impl Pipeline {
+    pub fn failed(&self) -> bool {
+        self.status == "failed"
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Compare {
+    pub commits: Vec<Commit>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub short_id: String,
+    pub title: String,
+    pub message: String,
+    pub author_name: String,
+    pub web_url: String,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct RepositoryEntry {
     pub r#type: String,
@@ -147,13 +295,57 @@ pub struct Project {
     pub id: u64,
     pub name: String,
     pub web_url: String,
+    pub default_branch: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
-pub struct FileUpdate {
+pub struct NewCommit {
     pub branch: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_branch: Option<String>,
     pub commit_message: String,
-    pub content: String,
+    pub actions: Vec<CommitAction>,
     pub author_email: String,
     pub author_name: String,
 }
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct CommitAction {
+    pub action: String,
+    pub file_path: String,
+    pub content: String,
+    /// GitLab rejects the commit when the file was changed after this commit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_id: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct RepositoryFile {
+    pub content: String,
+    pub last_commit_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct File {
+    pub content: String,
+    pub last_commit_id: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ErrorResponse {
+    pub message: serde_json::Value,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct NewMergeRequest {
+    pub source_branch: String,
+    pub target_branch: String,
+    pub title: String,
+    pub remove_source_branch: bool,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct MergeRequest {
+    pub iid: u64,
+    pub web_url: String,
+}
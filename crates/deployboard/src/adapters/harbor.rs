@@ -25,6 +25,28 @@ pub struct Artifact {
     pub repository_name: String,
     pub push_time: chrono::DateTime<chrono::Utc>,
     pub tags: Vec<Tag>,
+    pub extra_attrs: Option<ExtraAttrs>,
+}
+
+impl Artifact {
+    pub fn label(&self, name: &str) -> Option<&String> {
+        self.extra_attrs
+            .as_ref()
+            .and_then(|x| x.config.as_ref())
+            .and_then(|x| x.labels.as_ref())
+            .and_then(|x| x.get(name))
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ExtraAttrs {
+    pub config: Option<ImageConfig>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ImageConfig {
+    #[serde(rename = "Labels")]
+    pub labels: Option<std::collections::HashMap<String, String>>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
@@ -37,20 +59,9 @@ pub fn get_repositories(config: &ConnectionConfig, project_name: &str) -> Result
         "{}/api/v2.0/projects/{}/repositories?page=1&page_size=100",
         config.endpoint, project_name
     ));
-    request.headers.insert(
-        "authorization",
-        format!(
-            "Basic {}",
-            config
-                .token
-                .clone()
-                .unwrap_or_else(|| base64::encode(format!(
-                    "{}:{}",
-                    config.username.as_ref().unwrap(),
-                    config.password.as_ref().unwrap()
-                )))
-        ),
-    );
+    if let Some(authorization) = authorization(config) {
+        request.headers.insert("authorization", authorization);
+    }
 
     let response = super::http::fetch_blocking(&request)?;
     let artifact =
@@ -71,20 +82,9 @@ pub async fn get_artifact(
         urlencoding::encode(&urlencoding::encode(repository_name)),
         artifact_reference
     ));
-    request.headers.insert(
-        "authorization",
-        format!(
-            "Basic {}",
-            config
-                .token
-                .clone()
-                .unwrap_or_else(|| base64::encode(format!(
-                    "{}:{}",
-                    config.username.as_ref().unwrap(),
-                    config.password.as_ref().unwrap()
-                )))
-        ),
-    );
+    if let Some(authorization) = authorization(config) {
+        request.headers.insert("authorization", authorization);
+    }
 
     let response = super::http::fetch(&request, true).await?;
 
@@ -106,20 +106,9 @@ pub async fn get_artifacts(
         project_name,
         urlencoding::encode(&urlencoding::encode(repository_name)), sort, page_size,
     ));
-    request.headers.insert(
-        "authorization",
-        format!(
-            "Basic {}",
-            config
-                .token
-                .clone()
-                .unwrap_or_else(|| base64::encode(format!(
-                    "{}:{}",
-                    config.username.as_ref().unwrap(),
-                    config.password.as_ref().unwrap()
-                )))
-        ),
-    );
+    if let Some(authorization) = authorization(config) {
+        request.headers.insert("authorization", authorization);
+    }
 
     let response = super::http::fetch(&request, true).await?;
 
@@ -127,3 +116,13 @@ pub async fn get_artifacts(
         serde_json::from_slice::<Vec<Artifact>>(&response.bytes).map_err(|e| e.to_string())?;
     Ok(artifacts)
 }
+
+/// None when no credentials are configured, e.g. because the proxy adds them.
+fn authorization(config: &ConnectionConfig) -> Option<String> {
+    let credentials = match (&config.token, &config.username, &config.password) {
+        (Some(token), _, _) => token.clone(),
+        (None, Some(username), Some(password)) => base64::encode(format!("{}:{}", username, password)),
+        _ => return None,
+    };
+    Some(format!("Basic {}", credentials))
+}
@@ -1,34 +1,212 @@
-#[cfg(not(target_arch = "wasm32"))]
+/// `Record` passes requests through and stores the responses as fixtures, vault secret values redacted,
+/// `Replay` only answers from those fixtures so the board works without any backend.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default, PartialEq)]
+pub enum HttpMode {
+    #[default]
+    Live,
+    Record { fixtures: String },
+    Replay { fixtures: String },
+}
+
+static MODE: std::sync::RwLock<HttpMode> = std::sync::RwLock::new(HttpMode::Live);
+
+pub fn set_mode(mode: HttpMode) {
+    *MODE.write().unwrap() = mode;
+}
+
+pub fn mode() -> HttpMode {
+    MODE.read().unwrap().clone()
+}
+
+/// Endpoint prefixes rewritten to `{proxy}/{target}`, the proxy adds the credentials server side.
+static ROUTES: std::sync::RwLock<Vec<(String, String)>> = std::sync::RwLock::new(vec![]);
+
+/// Without a proxy requests go straight to the endpoints, which browsers block because of CORS.
+pub fn set_proxy(proxy: Option<&str>, endpoints: &[(&str, &str)]) {
+    let routes = match proxy {
+        Some(proxy) => endpoints
+            .iter()
+            .filter(|(endpoint, _)| !endpoint.is_empty())
+            .map(|(endpoint, target)| {
+                (
+                    endpoint.trim_end_matches('/').to_string(),
+                    format!("{}/{}", proxy.trim_end_matches('/'), target),
+                )
+            })
+            .collect(),
+        None => vec![],
+    };
+    *ROUTES.write().unwrap() = routes;
+}
+
+pub fn route(request: &ehttp::Request) -> ehttp::Request {
+    let mut request = request.clone();
+    if let Some((endpoint, target)) = ROUTES
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(endpoint, _)| request.url.starts_with(endpoint.as_str()))
+    {
+        request.url = format!("{}{}", target, &request.url[endpoint.len()..]);
+    }
+    request
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Fixture {
+    pub method: String,
+    pub url: String,
+    pub request_body: String,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Credentials live in headers, so they never end up in the key or the fixture.
+/// Vault writes carry secret values in the body and are never recorded, see `record`.
+pub fn fixture_path(fixtures: &str, request: &ehttp::Request) -> String {
+    use sha2::Digest;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(request.method.as_bytes());
+    hasher.update(request.url.as_bytes());
+    hasher.update(&request.body);
+    format!("{}/{:x}.json", fixtures, hasher.finalize())
+}
+
+pub const REDACTED: &str = "<redacted>";
+
+fn is_vault(request: &ehttp::Request) -> bool {
+    request.headers.get("X-Vault-Token").is_some()
+}
+
+/// Replaces every value of a KV v2 read, `{"data": {"data": {..}}}`, keeping the keys.
+pub fn redact_secret(body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut json = serde_json::from_slice::<serde_json::Value>(body).map_err(|e| e.to_string())?;
+    if let Some(data) = json
+        .get_mut("data")
+        .and_then(|x| x.get_mut("data"))
+        .and_then(|x| x.as_object_mut())
+    {
+        for value in data.values_mut() {
+            *value = serde_json::Value::String(REDACTED.to_string());
+        }
+    }
+    serde_json::to_vec(&json).map_err(|e| e.to_string())
+}
+
+/// Vault reads are stored with their values redacted, vault writes are skipped.
+pub fn record(fixtures: &str, request: &ehttp::Request, response: &ehttp::Response) -> Result<(), String> {
+    let mut body = response.bytes.clone();
+    if is_vault(request) {
+        if request.method != "GET" {
+            return Ok(());
+        }
+        if response.ok {
+            body = redact_secret(&body)?;
+        } else {
+            body = vec![];
+        }
+    }
+    let fixture = Fixture {
+        method: request.method.clone(),
+        url: request.url.clone(),
+        request_body: String::from_utf8_lossy(&request.body).to_string(),
+        status: response.status,
+        status_text: response.status_text.clone(),
+        headers: response.headers.headers.clone(),
+        body: String::from_utf8_lossy(&body).to_string(),
+    };
+    std::fs::create_dir_all(fixtures).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&fixture).map_err(|e| e.to_string())?;
+    std::fs::write(fixture_path(fixtures, request), text).map_err(|e| e.to_string())
+}
+
+/// Any request without a recorded fixture fails, a write must not look like it succeeded.
+pub fn replay(fixtures: &str, request: &ehttp::Request) -> Result<ehttp::Response, String> {
+    let path = fixture_path(fixtures, request);
+    let fixture = match std::fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str::<Fixture>(&text).map_err(|e| e.to_string())?,
+        Err(_) => {
+            return Err(format!("no fixture for {} {} ({})", request.method, request.url, path));
+        }
+    };
+    Ok(ehttp::Response {
+        url: fixture.url,
+        ok: (200..300).contains(&fixture.status),
+        status: fixture.status,
+        status_text: fixture.status_text,
+        headers: ehttp::Headers {
+            headers: fixture.headers,
+        },
+        bytes: fixture.body.into_bytes(),
+    })
+}
+
 pub fn fetch_blocking(request: &ehttp::Request) -> Result<ehttp::Response, String> {
-    let response = ehttp::fetch_blocking(request)?;
+    match mode() {
+        HttpMode::Live => live_fetch_blocking(request),
+        HttpMode::Record { fixtures } => {
+            let response = live_fetch_blocking(request)?;
+            if let Err(err) = record(&fixtures, request, &response) {
+                println!("err :{}", err);
+            }
+            Ok(response)
+        }
+        HttpMode::Replay { fixtures } => replay(&fixtures, request),
+    }
+}
+
+pub async fn fetch(request: &ehttp::Request, include: bool) -> Result<ehttp::Response, String> {
+    match mode() {
+        HttpMode::Live => live_fetch(request, include).await,
+        HttpMode::Record { fixtures } => {
+            let response = live_fetch(request, include).await?;
+            if let Err(err) = record(&fixtures, request, &response) {
+                println!("err :{}", err);
+            }
+            Ok(response)
+        }
+        HttpMode::Replay { fixtures } => replay(&fixtures, request),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn live_fetch_blocking(request: &ehttp::Request) -> Result<ehttp::Response, String> {
+    let response = ehttp::fetch_blocking(&route(request))?;
 
     return Ok(response);
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn fetch_blocking(request: &ehttp::Request) -> Result<ehttp::Response, String> {
-    return Err("not implemented".to_string());
+fn live_fetch_blocking(request: &ehttp::Request) -> Result<ehttp::Response, String> {
+    return Err(format!("blocking requests are not available on web, {} needs fetch", request.url));
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub async fn fetch(request: &ehttp::Request, include : bool) -> Result<ehttp::Response, String> {
-    let response = ehttp::fetch_blocking(request)?;
+async fn live_fetch(request: &ehttp::Request, include : bool) -> Result<ehttp::Response, String> {
+    let response = ehttp::fetch_blocking(&route(request))?;
 
     return Ok(response);
 }
 
+/// `include` sends cookies along, which the vault ui login relies on when no proxy is used.
 #[cfg(target_arch = "wasm32")]
-pub async fn fetch(request: &ehttp::Request, include : bool) -> Result<ehttp::Response, String> {
+async fn live_fetch(request: &ehttp::Request, include : bool) -> Result<ehttp::Response, String> {
     use wasm_bindgen_futures::wasm_bindgen::JsCast;
 
+    let request = &route(request);
     let opts = web_sys::RequestInit::new();
 
     opts.set_method(&request.method);
-    //opts.set_mode(web_sys::RequestMode::Cors);
+    opts.set_mode(web_sys::RequestMode::Cors);
     if include {
         opts.set_credentials(web_sys::RequestCredentials::Include); 
     }
-    
+    if !request.body.is_empty() {
+        let body = web_sys::js_sys::Uint8Array::from(request.body.as_slice());
+        opts.set_body(&body);
+    }
 
     let r = web_sys::Request::new_with_str_and_init(&request.url, &opts)
         .map_err(|x| format!("{:?}", x))?;
@@ -49,14 +227,59 @@ pub async fn fetch(request: &ehttp::Request, include : bool) -> Result<ehttp::Re
     let mut body = vec![0; uint8_array.length() as usize];
     uint8_array.copy_to(&mut body);
 
+    // only headers listed in Access-Control-Expose-Headers are visible, the proxy exposes x-next-page
+    let mut headers = vec![];
+    for name in ["content-type", "x-next-page", "x-total", "x-total-pages"] {
+        if let Ok(Some(value)) = resp.headers().get(name) {
+            headers.push((name.to_string(), value));
+        }
+    }
+
     let result = ehttp::Response {
         url: request.url.clone(),
-        ok: true,
+        ok: resp.ok(),
         status: resp.status(),
         status_text: resp.status_text(),
-        headers: ehttp::Headers::new(&[]),
+        headers: ehttp::Headers { headers },
         bytes: body,
     };
 
     return Ok(result);
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn record_redacts_vault_reads_and_skips_writes() {
+        let fixtures = std::env::temp_dir().join(format!("deployboard-record-{}", std::process::id()));
+        let fixtures = fixtures.to_str().unwrap();
+        let mut read = ehttp::Request::get("https://vault.test/v1/secret/data/apps/shop");
+        read.headers.insert("X-Vault-Token", "token");
+        let response = ehttp::Response {
+            url: read.url.clone(),
+            ok: true,
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: Default::default(),
+            bytes: br#"{"data":{"data":{"DB_PASSWORD":"hunter2"},"metadata":{"version":1}}}"#.to_vec(),
+        };
+        super::record(fixtures, &read, &response).unwrap();
+        let text = std::fs::read_to_string(super::fixture_path(fixtures, &read)).unwrap();
+        assert!(!text.contains("hunter2"));
+        assert!(text.contains(super::REDACTED));
+
+        let mut write = ehttp::Request::post(&read.url, br#"{"data":{"DB_PASSWORD":"hunter2"}}"#.to_vec());
+        write.headers.insert("X-Vault-Token", "token");
+        super::record(fixtures, &write, &response).unwrap();
+        assert!(!std::path::Path::new(&super::fixture_path(fixtures, &write)).exists());
+
+        std::fs::remove_dir_all(fixtures).unwrap();
+    }
+
+    #[test]
+    fn replay_fails_writes_without_fixture() {
+        let request = ehttp::Request::post("https://gitlab.test/api/v4/projects/42/repository/commits", b"{}".to_vec());
+        let err = super::replay(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"), &request).unwrap_err();
+        assert!(err.starts_with("no fixture for POST"));
+    }
+}
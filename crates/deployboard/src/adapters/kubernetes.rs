@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct ConnectionConfig {
+    pub endpoint: String,
+    pub token: Option<String>,
+    pub namespace: String,
+}
+
+/// Per env either a kubeconfig context or an explicit connection (e.g. a local stub server).
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct Config {
+    pub kubeconfig: Option<String>,
+    pub contexts: HashMap<String, String>,
+    pub connections: Option<HashMap<String, ConnectionConfig>>,
+}
+
+impl Config {
+    pub fn connection(&self, env: &str) -> Option<Result<ConnectionConfig, String>> {
+        if let Some(connection) = self.connections.as_ref().and_then(|x| x.get(env)) {
+            return Some(Ok(connection.clone()));
+        }
+        let context = self.contexts.get(env)?;
+        Some(connection_from_kubeconfig(
+            &self.kubeconfig.clone().unwrap_or_else(default_kubeconfig),
+            context,
+        ))
+    }
+}
+
+pub fn default_kubeconfig() -> String {
+    std::env::var("KUBECONFIG").unwrap_or_else(|_| {
+        format!("{}/.kube/config", std::env::var("HOME").unwrap_or_default())
+    })
+}
+
+/// Only token based users of clusters with a publicly trusted certificate are supported,
+/// anything else is an error instead of a connection that fails later, use a `connections` entry e.g. to `kubectl proxy` there.
+pub fn connection_from_kubeconfig(path: &str, context: &str) -> Result<ConnectionConfig, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let kubeconfig = serde_yaml::from_str::<KubeConfig>(&text).map_err(|e| e.to_string())?;
+    let context = kubeconfig
+        .contexts
+        .iter()
+        .find(|x| x.name == context)
+        .ok_or_else(|| format!("context {} not found in {}", context, path))?;
+    let cluster = kubeconfig
+        .clusters
+        .iter()
+        .find(|x| x.name == context.context.cluster)
+        .ok_or_else(|| format!("cluster {} not found in {}", context.context.cluster, path))?;
+    if cluster.cluster.certificate_authority.is_some() || cluster.cluster.certificate_authority_data.is_some() {
+        return Err(format!(
+            "cluster {} uses its own certificate authority, which is not supported, use `kubectl proxy` and a connection",
+            cluster.name
+        ));
+    }
+    let user = kubeconfig
+        .users
+        .iter()
+        .find(|x| x.name == context.context.user)
+        .ok_or_else(|| format!("user {} not found in {}", context.context.user, path))?;
+    let token = user.user.token.clone();
+    if token.is_none() {
+        let method = if user.user.client_certificate.is_some() || user.user.client_certificate_data.is_some() {
+            "a client certificate"
+        } else if user.user.exec.is_some() || user.user.auth_provider.is_some() {
+            "an exec or auth provider plugin"
+        } else {
+            "no token"
+        };
+        return Err(format!(
+            "user {} authenticates with {}, only tokens are supported, use `kubectl proxy` and a connection",
+            user.name, method
+        ));
+    }
+    Ok(ConnectionConfig {
+        endpoint: cluster.cluster.server.trim_end_matches('/').to_string(),
+        token,
+        namespace: context
+            .context
+            .namespace
+            .clone()
+            .unwrap_or_else(|| "default".to_string()),
+    })
+}
+
+pub async fn get_workload(config: &ConnectionConfig, name: &str) -> Result<Workload, String> {
+    let mut errors = vec![];
+    for kind in ["deployments", "statefulsets"] {
+        match get_workload_of_kind(config, kind, name).await {
+            Ok(workload) => return Ok(workload),
+            Err(err) => errors.push(format!("{}: {}", kind, err)),
+        }
+    }
+    Err(errors.join("\n"))
+}
+
+pub async fn get_workload_of_kind(
+    config: &ConnectionConfig,
+    kind: &str,
+    name: &str,
+) -> Result<Workload, String> {
+    let mut request = ehttp::Request::get(format!(
+        "{}/apis/apps/v1/namespaces/{}/{}/{}",
+        config.endpoint, config.namespace, kind, name
+    ));
+    if let Some(token) = &config.token {
+        request
+            .headers
+            .insert("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = super::http::fetch(&request, false).await?;
+    if response.status != 200 {
+        return Err(format!("response was not ok: {}", response.status_text));
+    }
+    let workload =
+        serde_json::from_slice::<Workload>(&response.bytes).map_err(|e| e.to_string())?;
+    Ok(workload)
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Workload {
+    pub kind: String,
+    pub spec: WorkloadSpec,
+}
+
+impl Workload {
+    pub fn containers(&self) -> &Vec<Container> {
+        &self.spec.template.spec.containers
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct WorkloadSpec {
+    pub template: PodTemplate,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct PodTemplate {
+    pub spec: PodSpec,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct PodSpec {
+    pub containers: Vec<Container>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Container {
+    pub name: String,
+    pub image: String,
+    #[serde(default)]
+    pub env: Vec<EnvVar>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct KubeConfig {
+    #[serde(default)]
+    pub clusters: Vec<NamedCluster>,
+    #[serde(default)]
+    pub contexts: Vec<NamedContext>,
+    #[serde(default)]
+    pub users: Vec<NamedUser>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct NamedCluster {
+    pub name: String,
+    pub cluster: Cluster,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Cluster {
+    pub server: String,
+    pub certificate_authority: Option<String>,
+    pub certificate_authority_data: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct NamedContext {
+    pub name: String,
+    pub context: Context,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Context {
+    pub cluster: String,
+    pub user: String,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct NamedUser {
+    pub name: String,
+    pub user: User,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct User {
+    pub token: Option<String>,
+    pub client_certificate: Option<String>,
+    pub client_certificate_data: Option<String>,
+    pub exec: Option<serde_yaml::Value>,
+    pub auth_provider: Option<serde_yaml::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    fn connection() -> super::ConnectionConfig {
+        crate::adapters::http::set_mode(crate::adapters::http::HttpMode::Replay {
+            fixtures: concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures").to_string(),
+        });
+        super::ConnectionConfig {
+            endpoint: "https://k8s.test".to_string(),
+            token: None,
+            namespace: "shop".to_string(),
+        }
+    }
+
+    #[test]
+    fn get_deployment() {
+        let workload = futures::executor::block_on(super::get_workload(&connection(), "shop-api")).unwrap();
+        assert_eq!(workload.kind, "Deployment");
+        let container = &workload.containers()[0];
+        assert_eq!(container.image, "registry.test/apps/shop:1.2.0");
+        assert_eq!(container.env.len(), 3);
+        assert_eq!(container.env[2].value, None);
+    }
+
+    #[test]
+    fn get_statefulset_when_no_deployment() {
+        let workload = futures::executor::block_on(super::get_workload(&connection(), "db")).unwrap();
+        assert_eq!(workload.kind, "StatefulSet");
+        assert_eq!(workload.containers()[0].image, "registry.test/apps/postgres:16.4");
+    }
+
+    /// Each test writes its own file, tests run in parallel.
+    fn kubeconfig(test: &str, cluster: &str, user: &str) -> Result<super::ConnectionConfig, String> {
+        let path = std::env::temp_dir().join(format!("deployboard-{}-{}", test, std::process::id()));
+        let text = format!(
+            "clusters:\n- name: dev\n  cluster:\n    server: https://k8s.test/\n{}\
+             contexts:\n- name: dev\n  context: {{ cluster: dev, user: dev }}\n\
+             users:\n- name: dev\n  user:\n{}",
+            cluster, user
+        );
+        std::fs::write(&path, text).unwrap();
+        let connection = super::connection_from_kubeconfig(path.to_str().unwrap(), "dev");
+        std::fs::remove_file(&path).unwrap();
+        connection
+    }
+
+    #[test]
+    fn kubeconfig_with_token() {
+        let connection = kubeconfig("token", "", "    token: abc\n").unwrap();
+        assert_eq!(connection.endpoint, "https://k8s.test");
+        assert_eq!(connection.token.as_deref(), Some("abc"));
+        assert_eq!(connection.namespace, "default");
+    }
+
+    #[test]
+    fn kubeconfig_with_client_certificate_is_an_error() {
+        let err = kubeconfig("client-certificate", "", "    client-certificate-data: Y2VydA==\n    client-key-data: a2V5\n").unwrap_err();
+        assert!(err.contains("client certificate"), "{}", err);
+    }
+
+    #[test]
+    fn kubeconfig_with_custom_ca_is_an_error() {
+        let err = kubeconfig("ca", "    certificate-authority-data: Y2E=\n", "    token: abc\n").unwrap_err();
+        assert!(err.contains("certificate authority"), "{}", err);
+    }
+}
@@ -25,28 +25,42 @@ pub async fn get_secret(
     config: &ConnectionConfig,
     path: &str,
 ) -> Result<std::collections::BTreeMap<String, String>, String> {
+    get_secret_version(config, path).await.map(|x| x.data)
+}
+
+pub async fn get_secret_version(config: &ConnectionConfig, path: &str) -> Result<Secret, String> {
     let mut request = ehttp::Request::get(format!("{}/v1/secret/data/{}", config.endpoint, path));
     request.headers.insert("X-Vault-Token", &config.token);
 
     let response = super::http::fetch(&request, true).await?;
     let response =
         serde_json::from_slice::<GetSecretResponse>(&response.bytes).map_err(|e| e.to_string())?;
-    Ok(response.data.data)
+    Ok(response.data)
 }
 
-pub fn update_secret(
+/// A KV v2 write replaces the whole secret, with `cas` vault refuses it when the secret got a newer version.
+pub async fn update_secret(
     config: &ConnectionConfig,
     path: &str,
     data: &std::collections::BTreeMap<String, String>,
+    cas: Option<u64>,
 ) -> Result<(), String> {
-    let body = serde_json::to_vec(&Secret { data: data.clone() }).map_err(|e| e.to_string())?;
+    let body = serde_json::to_vec(&UpdateSecret {
+        options: cas.map(|cas| WriteOptions { cas }),
+        data: data.clone(),
+    })
+    .map_err(|e| e.to_string())?;
     let mut request =
         ehttp::Request::post(format!("{}/v1/secret/data/{}", config.endpoint, path), body);
     request.headers.insert("X-Vault-Token", &config.token);
 
-    let response = super::http::fetch_blocking(&request)?;
+    let response = super::http::fetch(&request, true).await?;
     if !response.ok {
-        return Err("Saving not succeed response was not ok".to_owned());
+        return Err(format!(
+            "Saving not succeed response was not ok: {} {}",
+            response.status_text,
+            String::from_utf8_lossy(&response.bytes)
+        ));
     }
     Ok(())
 }
@@ -59,6 +73,25 @@ pub struct GetSecretResponse {
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Secret {
     pub data: std::collections::BTreeMap<String, String>,
+    #[serde(default)]
+    pub metadata: Option<SecretMetadata>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SecretMetadata {
+    pub version: u64,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct UpdateSecret {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<WriteOptions>,
+    pub data: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct WriteOptions {
+    pub cas: u64,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -0,0 +1,162 @@
+use hmac::Mac;
+use sha2::Digest;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+pub enum AuditKind {
+    Gitlab,
+    Vault,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct AuditEntry {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub user: String,
+    pub kind: AuditKind,
+    pub target: String,
+    /// Keyed with the local audit key, so low-entropy secrets cannot be recovered by hashing guesses.
+    pub old_hash: String,
+    pub new_hash: String,
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        self.target.to_lowercase().contains(&filter)
+            || self.user.to_lowercase().contains(&filter)
+            || format!("{:?}", self.kind).to_lowercase().contains(&filter)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    pub path: String,
+    pub user: String,
+}
+
+impl AuditLog {
+    pub fn new(config: &crate::config::Config) -> Self {
+        let user = if config.gitlab.author.name.is_empty() {
+            std::env::var("USER").unwrap_or_default()
+        } else {
+            config.gitlab.author.name.clone()
+        };
+        Self {
+            path: config
+                .audit_log
+                .clone()
+                .unwrap_or_else(|| "deployboard-audit.jsonl".to_string()),
+            user,
+        }
+    }
+
+    /// Appends one line per write attempt, a failing log write is reported but never blocks the change itself.
+    /// Values are only stored as keyed hashes.
+    pub fn record<T>(
+        &self,
+        kind: AuditKind,
+        target: &str,
+        old: &str,
+        new: &str,
+        result: &Result<T, String>,
+    ) -> Result<(), String> {
+        let key = self.key()?;
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now(),
+            user: self.user.clone(),
+            kind,
+            target: target.to_string(),
+            old_hash: keyed_hash(&key, old),
+            new_hash: keyed_hash(&key, new),
+            error: result.as_ref().err().cloned(),
+        };
+        let mut line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+        storage::append(&self.path, &line)
+    }
+
+    pub fn read(&self) -> Result<Vec<AuditEntry>, String> {
+        let text = storage::read(&self.path)?.unwrap_or_default();
+        Ok(text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .collect())
+    }
+
+    /// The hmac key lives next to the log and is created with the first entry.
+    fn key(&self) -> Result<Vec<u8>, String> {
+        let path = format!("{}.key", self.path);
+        if let Some(text) = storage::read(&path)? {
+            return Ok(text.trim().as_bytes().to_vec());
+        }
+        let key = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        storage::write(&path, &key)?;
+        Ok(key.into_bytes())
+    }
+}
+
+pub fn keyed_hash(key: &[u8], value: &str) -> String {
+    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key).expect("hmac takes keys of any length");
+    mac.update(value.as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Unkeyed, only for values that are not secret like file contents.
+pub fn hash(value: &str) -> String {
+    format!("{:x}", sha2::Sha256::digest(value.as_bytes()))
+}
+
+pub fn export(path: &str, entries: &[&AuditEntry]) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Files on native, local storage on web where there is no file system.
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::io::Write;
+
+    pub fn read(path: &str) -> Result<Option<String>, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Some(text)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("{}: {}", path, err)),
+        }
+    }
+
+    pub fn write(path: &str, text: &str) -> Result<(), String> {
+        std::fs::write(path, text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn append(path: &str, text: &str) -> Result<(), String> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("{}: {}", path, e))?;
+        file.write_all(text.as_bytes()).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    fn local_storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| "local storage is not available".to_string())
+    }
+
+    pub fn read(path: &str) -> Result<Option<String>, String> {
+        local_storage()?.get_item(path).map_err(|e| format!("{}: {:?}", path, e))
+    }
+
+    pub fn write(path: &str, text: &str) -> Result<(), String> {
+        local_storage()?.set_item(path, text).map_err(|e| format!("{}: {:?}", path, e))
+    }
+
+    pub fn append(path: &str, text: &str) -> Result<(), String> {
+        let mut all = read(path)?.unwrap_or_default();
+        all.push_str(text);
+        write(path, &all)
+    }
+}
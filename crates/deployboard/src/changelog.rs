@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+pub const REVISION_LABEL: &str = "org.opencontainers.image.revision";
+
+/// Maps a harbor repository path to the gitlab project it is built from.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct ChangelogConfig {
+    pub tag_commit_regex: Option<String>,
+    pub projects: HashMap<String, String>,
+}
+
+impl ChangelogConfig {
+    /// The OCI revision label wins, otherwise the `commit` group of `tag_commit_regex` is taken from the first matching tag.
+    pub fn revision(&self, artifact: &crate::adapters::harbor::Artifact) -> Option<String> {
+        if let Some(revision) = artifact.label(REVISION_LABEL) {
+            return Some(revision.clone());
+        }
+        let regex = regex::Regex::new(self.tag_commit_regex.as_ref()?).ok()?;
+        artifact.tags.iter().find_map(|tag| {
+            regex
+                .captures(&tag.name)
+                .and_then(|x| x.name("commit"))
+                .map(|x| x.as_str().to_string())
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Changelog {
+    pub commits: Vec<crate::adapters::gitlab::Commit>,
+    pub merge_requests: Vec<String>,
+}
+
+pub fn get_changelog(
+    config: &crate::config::Config,
+    image_path: &str,
+    old: &crate::adapters::harbor::Artifact,
+    new: &crate::adapters::harbor::Artifact,
+    ctx: egui::Context,
+) -> poll_promise::Promise<Result<Changelog, String>> {
+    let (sender, promise) = poll_promise::Promise::new();
+    let project = config.changelog.projects.get(image_path).cloned();
+    let from = config.changelog.revision(old);
+    let to = config.changelog.revision(new);
+    let connection = config.gitlab.connection.clone();
+    let image_path = image_path.to_string();
+    common::execute(async move {
+        let result = match (project, from, to) {
+            (None, _, _) => Err(format!("no gitlab project configured for {}", image_path)),
+            (_, None, _) | (_, _, None) => Err("no revision found for the tags".to_string()),
+            (Some(project), Some(from), Some(to)) => {
+                crate::adapters::gitlab::compare(&connection, &project, &from, &to)
+                    .await
+                    .map(|compare| Changelog {
+                        merge_requests: merge_requests(&compare.commits),
+                        commits: compare.commits,
+                    })
+            }
+        };
+        sender.send(result);
+        ctx.request_repaint();
+    });
+    promise
+}
+
+/// Merge commits created by gitlab end with "See merge request group/project!123".
+pub fn merge_requests(commits: &[crate::adapters::gitlab::Commit]) -> Vec<String> {
+    let regex = regex::Regex::new(r"See merge request (?<mr>\S+!\d+)").unwrap();
+    commits
+        .iter()
+        .filter_map(|commit| regex.captures(&commit.message))
+        .filter_map(|x| x.name("mr").map(|x| x.as_str().to_string()))
+        .collect()
+}
+
+pub fn show_changelog(
+    ui: &mut egui::Ui,
+    changelog: &poll_promise::Promise<Result<Changelog, String>>,
+) {
+    ui.label(egui::RichText::new("changelog").strong());
+    match changelog.ready() {
+        Some(Ok(changelog)) => {
+            if !changelog.merge_requests.is_empty() {
+                ui.label(format!("merge requests: {}", changelog.merge_requests.join(", ")));
+            }
+            egui::ScrollArea::vertical()
+                .id_salt("changelog")
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    if changelog.commits.is_empty() {
+                        ui.label("no commits");
+                    }
+                    for commit in changelog.commits.iter().rev() {
+                        ui.horizontal(|ui| {
+                            ui.hyperlink_to(&commit.short_id, &commit.web_url);
+                            ui.label(&commit.title);
+                            ui.weak(&commit.author_name);
+                        });
+                    }
+                });
+        }
+        Some(Err(err)) => {
+            ui.weak(err);
+        }
+        None => {
+            ui.horizontal(|ui| {
+                ui.label("loading");
+                ui.spinner();
+            });
+        }
+    }
+}
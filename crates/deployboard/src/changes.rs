@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+
+/// A staged edit, nothing is written until the change set is applied.
+#[derive(Clone)]
+pub struct Change {
+    pub description: String,
+    pub env: String,
+    pub commit_message: String,
+    pub require_merge_request: bool,
+    pub kind: ChangeKind,
+}
+
+#[derive(Clone)]
+pub enum ChangeKind {
+    /// Yaml edits instead of the whole file, so several changes of one file stack.
+    File {
+        project: String,
+        path: String,
+        raw: String,
+        edits: Vec<(crate::yaml::Path, serde_yaml::Value)>,
+    },
+    /// Only the keys that differ between `old` and `new` are written.
+    Secret {
+        path: String,
+        old: BTreeMap<String, String>,
+        new: BTreeMap<String, String>,
+    },
+}
+
+impl Change {
+    pub fn target(&self) -> String {
+        match &self.kind {
+            ChangeKind::File { project, path, .. } => format!("{}/{}", project, path),
+            ChangeKind::Secret { path, .. } => format!("vault {}", path),
+        }
+    }
+}
+
+/// All changes to one file, `raw` is the content the first edit was staged on.
+pub struct StagedFile {
+    pub project: String,
+    pub path: String,
+    pub raw: String,
+    pub edits: Vec<(crate::yaml::Path, serde_yaml::Value)>,
+    pub commit_messages: Vec<String>,
+    pub require_merge_request: bool,
+}
+
+impl StagedFile {
+    pub fn commit_message(&self) -> String {
+        if self.commit_messages.len() == 1 {
+            return self.commit_messages[0].clone();
+        }
+        format!(
+            "{} changes to {}\n\n{}",
+            self.commit_messages.len(),
+            self.path,
+            self.commit_messages
+                .iter()
+                .map(|x| format!("- {}", x))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+
+    /// Replays the edits onto `current`, the file as it is now. Edits of values that were changed
+    /// since staging are a conflict, anything else changed in between is kept.
+    pub fn rebase(&self, current: &str) -> Result<String, String> {
+        let staged = serde_yaml::from_str::<serde_yaml::Value>(&self.raw).map_err(|e| e.to_string())?;
+        let mut yaml = serde_yaml::from_str::<serde_yaml::Value>(current).map_err(|e| e.to_string())?;
+        for (field, _) in self.edits.iter() {
+            if crate::yaml::get_at(&staged, field) != crate::yaml::get_at(&yaml, field) {
+                return Err(format!(
+                    "Conflict in {}: {} was changed since it was staged, discard and stage again",
+                    self.path,
+                    crate::yaml::P(field)
+                ));
+            }
+        }
+        for (field, value) in self.edits.iter() {
+            crate::yaml::set_field(&mut yaml, field, value, false);
+        }
+        serde_yaml::to_string(&yaml).map_err(|e| e.to_string())
+    }
+
+    /// Fetches the file and commits the rebased edits, with `last_commit_id` so a change after the fetch fails too.
+    pub async fn commit(
+        self,
+        gitlab: crate::adapters::gitlab::Config,
+        audit: crate::audit::AuditLog,
+    ) -> crate::models::JobResult {
+        let current =
+            match crate::adapters::gitlab::get_file(&gitlab.connection, &self.project, &self.path, "main").await {
+                Ok(current) => current,
+                Err(err) => return crate::models::JobResult::error(format!("Error Updating {}:\n {}", self.path, err)),
+            };
+        let new_text = match self.rebase(&current.content) {
+            Ok(new_text) => new_text,
+            Err(err) => return crate::models::JobResult::error(err),
+        };
+        let commit_message = self.commit_message();
+        crate::core::commit_file_job(
+            gitlab,
+            audit,
+            crate::core::FileCommit {
+                project: self.project,
+                path: self.path.clone(),
+                raw: current.content,
+                new_text,
+                last_commit_id: Some(current.last_commit_id),
+                commit_message,
+                require_merge_request: self.require_merge_request,
+            },
+            self.path,
+        )
+        .await
+    }
+}
+
+/// All changes to one vault path, merged in staging order.
+pub struct SecretWrite {
+    pub path: String,
+    pub old: BTreeMap<String, String>,
+    pub new: BTreeMap<String, String>,
+}
+
+impl SecretWrite {
+    /// Applies the keys this write changes onto `current`, a key changed by someone else since staging is a conflict.
+    pub fn rebase(&self, current: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+        let mut result = current.clone();
+        for key in self.old.keys().chain(self.new.keys()) {
+            let (old, new) = (self.old.get(key), self.new.get(key));
+            if old == new {
+                continue;
+            }
+            if current.get(key) != old {
+                return Err(format!(
+                    "Conflict in {}: {} was changed since it was staged, discard and stage again",
+                    self.path, key
+                ));
+            }
+            match new {
+                Some(value) => result.insert(key.clone(), value.clone()),
+                None => result.remove(key),
+            };
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Default)]
+pub struct ChangeSet {
+    pub changes: Vec<Change>,
+    pub show: bool,
+}
+
+impl ChangeSet {
+    pub fn stage(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.changes.len() {
+            self.changes.swap(index - 1, index);
+        }
+    }
+
+    pub fn move_down(&mut self, index: usize) {
+        if index + 1 < self.changes.len() {
+            self.changes.swap(index, index + 1);
+        }
+    }
+
+    pub fn discard(&mut self, index: usize) {
+        if index < self.changes.len() {
+            self.changes.remove(index);
+        }
+    }
+
+    /// One commit per file, in the order the file was first staged.
+    pub fn files(changes: &[Change]) -> Vec<StagedFile> {
+        let mut files: Vec<StagedFile> = vec![];
+        for change in changes.iter() {
+            let ChangeKind::File { project, path, raw, edits } = &change.kind else {
+                continue;
+            };
+            let index = match files.iter().position(|x| &x.project == project && &x.path == path) {
+                Some(index) => index,
+                None => {
+                    files.push(StagedFile {
+                        project: project.clone(),
+                        path: path.clone(),
+                        raw: raw.clone(),
+                        edits: vec![],
+                        commit_messages: vec![],
+                        require_merge_request: false,
+                    });
+                    files.len() - 1
+                }
+            };
+            let file = &mut files[index];
+            file.edits.extend(edits.iter().cloned());
+            file.commit_messages.push(change.commit_message.clone());
+            file.require_merge_request |= change.require_merge_request;
+        }
+        files
+    }
+
+    pub fn secrets(changes: &[Change]) -> Vec<SecretWrite> {
+        let mut secrets: Vec<SecretWrite> = vec![];
+        for change in changes.iter() {
+            let ChangeKind::Secret { path, old, new } = &change.kind else {
+                continue;
+            };
+            let index = match secrets.iter().position(|x| &x.path == path) {
+                Some(index) => index,
+                None => {
+                    secrets.push(SecretWrite {
+                        path: path.clone(),
+                        old: old.clone(),
+                        new: old.clone(),
+                    });
+                    secrets.len() - 1
+                }
+            };
+            let secret = &mut secrets[index];
+            for (key, value) in new.iter() {
+                if old.get(key) != Some(value) {
+                    secret.new.insert(key.clone(), value.clone());
+                }
+            }
+            for key in old.keys() {
+                if !new.contains_key(key) {
+                    secret.new.remove(key);
+                }
+            }
+        }
+        secrets
+    }
+}
+
+/// Writes the whole change set as one job, changes to envs that got blocked since staging are skipped.
+pub fn apply(app: &mut crate::App) {
+    let mut errors = vec![];
+    let mut changes = vec![];
+    for change in std::mem::take(&mut app.changes.changes) {
+        match app.config.protection.blocked(&change.env) {
+            Some(reason) => errors.push(format!("Skipped {}: {}", change.description, reason)),
+            None => changes.push(change),
+        }
+    }
+    let files = ChangeSet::files(&changes);
+    let secrets = ChangeSet::secrets(&changes);
+    let gitlab = app.config.gitlab.clone();
+    let vault = app.config.vault.connection.clone();
+    let audit = app.audit.clone();
+    app.jobs.spawn(async move {
+        let mut succeeded = vec![];
+        let mut warnings = vec![];
+        let mut reload = false;
+        for file in files {
+            let result = file.commit(gitlab.clone(), audit.clone()).await;
+            reload |= result.reload;
+            warnings.extend(result.warnings);
+            match result.message {
+                Ok(message) => succeeded.push(message),
+                Err(message) => errors.push(message),
+            }
+        }
+        for secret in secrets {
+            // read again, the write replaces the whole secret and would drop keys changed since staging
+            let current = match crate::adapters::vault::get_secret_version(&vault, &secret.path).await {
+                Ok(current) => current,
+                Err(err) => {
+                    errors.push(format!("Error Updating {}:\n {}", secret.path, err));
+                    continue;
+                }
+            };
+            let new = match secret.rebase(&current.data) {
+                Ok(new) => new,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+            let cas = current.metadata.as_ref().map(|x| x.version);
+            let update_result = crate::adapters::vault::update_secret(&vault, &secret.path, &new, cas).await;
+            if let Err(err) = audit.record(
+                crate::audit::AuditKind::Vault,
+                &secret.path,
+                &serde_json::to_string_pretty(&current.data).unwrap_or_default(),
+                &serde_json::to_string_pretty(&new).unwrap_or_default(),
+                &update_result,
+            ) {
+                warnings.push(format!("Error writing audit log for {}:\n {}", secret.path, err));
+            }
+            match update_result {
+                Err(err) => errors.push(format!("Error Updating {}:\n {}", secret.path, err)),
+                Ok(()) => {
+                    reload = true;
+                    succeeded.push(format!("Updating {} succeed!", secret.path));
+                }
+            }
+        }
+        if errors.is_empty() {
+            crate::models::JobResult::success(succeeded.join("\n"), reload).with_warnings(warnings)
+        } else {
+            crate::models::JobResult {
+                message: Err(succeeded.into_iter().chain(errors).collect::<Vec<_>>().join("\n")),
+                reload,
+                warnings,
+            }
+        }
+    });
+}
+
+enum Reorder {
+    Up,
+    Down,
+    Discard,
+}
+
+pub fn show_changes(app: &mut crate::App, ctx: &mut common::app::Context<'_>) {
+    if app.changes.show {
+        let mut apply_all = false;
+        let mut discard_all = false;
+        let mut action = None;
+        let empty = app.changes.changes.is_empty();
+        let modal = egui::Modal::new(egui::Id::new("changes")).show(ctx.ui.ctx(), |ui| {
+            ui.set_width(ctx.ui.available_width() * 0.8);
+
+            ui.heading(format!("Pending changes ({})", app.changes.changes.len()));
+            egui::ScrollArea::vertical().max_height(ctx.ui.ctx().available_rect().height() * 0.7).show(ui, |ui| {
+                if app.changes.changes.is_empty() {
+                    ui.label("nothing staged");
+                }
+                let count = app.changes.changes.len();
+                for (i, change) in app.changes.changes.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(i > 0, egui::Button::new("⬆")).clicked() {
+                            action = Some((i, Reorder::Up));
+                        }
+                        if ui.add_enabled(i + 1 < count, egui::Button::new("⬇")).clicked() {
+                            action = Some((i, Reorder::Down));
+                        }
+                        if ui.button("🗑").clicked() {
+                            action = Some((i, Reorder::Discard));
+                        }
+                        ui.label(egui::RichText::new(&change.env).strong());
+                        ui.label(&change.description);
+                        ui.weak(change.target());
+                        if change.require_merge_request {
+                            ui.weak("(merge request)");
+                        }
+                    });
+                    if let Some(reason) = app.config.protection.blocked(&change.env) {
+                        ui.colored_label(ui.visuals().error_fg_color, reason);
+                    }
+                    ui.monospace(&change.commit_message);
+                    ui.separator();
+                }
+            });
+
+            egui::Sides::new().show(
+                ui,
+                |ui| {
+                    if ui.add_enabled(!empty, egui::Button::new("Discard all")).clicked() {
+                        discard_all = true;
+                    }
+                },
+                |ui| {
+                    if ui.add_enabled(!empty, egui::Button::new("Apply all")).clicked() {
+                        apply_all = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        app.changes.show = false;
+                    }
+                },
+            );
+        });
+
+        match action {
+            Some((i, Reorder::Up)) => app.changes.move_up(i),
+            Some((i, Reorder::Down)) => app.changes.move_down(i),
+            Some((i, Reorder::Discard)) => app.changes.discard(i),
+            None => {}
+        }
+
+        if discard_all {
+            app.changes.changes.clear();
+        }
+
+        if apply_all {
+            apply(app);
+            app.changes.show = false;
+        }
+
+        if modal.should_close() {
+            app.changes.show = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(raw: &str, field: &str, value: &str, message: &str) -> Change {
+        Change {
+            description: message.to_string(),
+            env: "dev".to_string(),
+            commit_message: message.to_string(),
+            require_merge_request: false,
+            kind: ChangeKind::File {
+                project: "42".to_string(),
+                path: "shop/values-dev.yaml".to_string(),
+                raw: raw.to_string(),
+                edits: vec![(crate::yaml::path_from_str(field), serde_yaml::Value::String(value.to_string()))],
+            },
+        }
+    }
+
+    fn secret(old: &[(&str, &str)], new: &[(&str, &str)]) -> Change {
+        Change {
+            description: "secret".to_string(),
+            env: "dev".to_string(),
+            commit_message: "secret".to_string(),
+            require_merge_request: false,
+            kind: ChangeKind::Secret {
+                path: "apps/shop/dev".to_string(),
+                old: map(old),
+                new: map(new),
+            },
+        }
+    }
+
+    fn map(x: &[(&str, &str)]) -> BTreeMap<String, String> {
+        x.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    const RAW: &str = "image:\n  tag: v1\nreplicas: '1'\n";
+
+    #[test]
+    fn two_edits_of_one_file_are_one_commit() {
+        let files = ChangeSet::files(&[
+            file(RAW, "image/tag", "v2", "shop v2"),
+            file(RAW, "replicas", "2", "two replicas"),
+        ]);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].commit_message(), "2 changes to shop/values-dev.yaml\n\n- shop v2\n- two replicas");
+        assert_eq!(files[0].rebase(RAW).unwrap(), "image:\n  tag: v2\nreplicas: '2'\n");
+    }
+
+    #[test]
+    fn unrelated_upstream_change_is_kept() {
+        let files = ChangeSet::files(&[file(RAW, "image/tag", "v2", "shop v2")]);
+        let current = "image:\n  tag: v1\nreplicas: '3'\n";
+        assert_eq!(files[0].rebase(current).unwrap(), "image:\n  tag: v2\nreplicas: '3'\n");
+    }
+
+    #[test]
+    fn conflicting_upstream_change_is_an_error() {
+        let files = ChangeSet::files(&[file(RAW, "image/tag", "v2", "shop v2")]);
+        let err = files[0].rebase("image:\n  tag: v3\nreplicas: '1'\n").unwrap_err();
+        assert!(err.starts_with("Conflict in shop/values-dev.yaml"), "{}", err);
+        // a key deleted upstream conflicts too
+        assert!(files[0].rebase("replicas: '1'\n").is_err());
+    }
+
+    #[test]
+    fn secret_writes_merge_in_staging_order() {
+        let secrets = ChangeSet::secrets(&[
+            secret(&[("A", "1"), ("B", "2")], &[("A", "10"), ("B", "2")]),
+            secret(&[("A", "1"), ("B", "2")], &[("A", "1")]),
+        ]);
+        assert_eq!(secrets.len(), 1);
+        assert_eq!(secrets[0].new, map(&[("A", "10")]));
+
+        // C was added upstream and is kept, the deleted key B is removed
+        let current = map(&[("A", "1"), ("B", "2"), ("C", "3")]);
+        assert_eq!(secrets[0].rebase(&current).unwrap(), map(&[("A", "10"), ("C", "3")]));
+    }
+
+    #[test]
+    fn secret_changed_upstream_is_a_conflict() {
+        let secrets = ChangeSet::secrets(&[secret(&[("A", "1"), ("B", "2")], &[("A", "10"), ("B", "2")])]);
+        let err = secrets[0].rebase(&map(&[("A", "5"), ("B", "2")])).unwrap_err();
+        assert!(err.contains("A was changed since it was staged"), "{}", err);
+        // only the keys of the write count
+        assert!(secrets[0].rebase(&map(&[("A", "1"), ("B", "7")])).is_ok());
+    }
+}
@@ -7,6 +7,28 @@ pub struct Config {
     pub harbor: crate::adapters::harbor::Config,
     pub envs: Vec<String>,
     pub sources: Vec<Source>,
+    #[serde(default)]
+    pub workspace: String,
+    #[serde(default)]
+    pub workspaces: Vec<Workspace>,
+    pub audit_log: Option<String>,
+    pub kubernetes: Option<crate::adapters::kubernetes::Config>,
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    #[serde(default)]
+    pub groups: std::collections::BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub watch: crate::watch::WatchConfig,
+    #[serde(default)]
+    pub changelog: crate::changelog::ChangelogConfig,
+    #[serde(default)]
+    pub protection: crate::protection::ProtectionConfig,
+    #[serde(default)]
+    pub http: crate::adapters::http::HttpMode,
+    #[serde(default)]
+    pub watched_fields: Vec<WatchedField>,
+    /// Base url of `deployboard-proxy`, required on web where the endpoints are blocked by CORS.
+    pub proxy: Option<String>,
 }
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct Source {
@@ -17,4 +39,218 @@ pub struct Source {
     pub argocd_endpoints: Option<HashMap<String, String>>,
     pub argocd_prefix: Option<String>,
     pub env: Option<String>,
-}
\ No newline at end of file
+    pub auto_update_env: Option<String>,
+    /// Kubernetes workload name per deployment name, the lowercased deployment name otherwise.
+    pub workloads: Option<HashMap<String, String>>,
+}
+
+/// An extra value of the deployment file shown and editable per env, `path` is a query like `image_path`.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct WatchedField {
+    pub name: String,
+    pub path: String,
+    pub r#type: FieldType,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Number,
+    Bool,
+    String,
+    Enum(Vec<String>),
+}
+
+impl FieldType {
+    pub fn parse(&self, text: &str) -> Result<serde_yaml::Value, String> {
+        match self {
+            FieldType::Number => {
+                if let Ok(number) = text.parse::<i64>() {
+                    return Ok(serde_yaml::Value::Number(number.into()));
+                }
+                text.parse::<f64>()
+                    .map(|number| serde_yaml::Value::Number(number.into()))
+                    .map_err(|_| format!("{} is not a number", text))
+            }
+            FieldType::Bool => text
+                .parse::<bool>()
+                .map(serde_yaml::Value::Bool)
+                .map_err(|_| format!("{} is not a bool", text)),
+            FieldType::String => Ok(serde_yaml::Value::String(text.to_string())),
+            FieldType::Enum(values) => {
+                if values.iter().any(|x| x == text) {
+                    Ok(serde_yaml::Value::String(text.to_string()))
+                } else {
+                    Err(format!("{} is not one of {}", text, values.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct Workspace {
+    pub name: String,
+    pub envs: Vec<String>,
+    pub sources: Vec<Source>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProjectAction {
+    TogglePin(String),
+    AddToGroup(String, String),
+    RemoveFromGroup(String, String),
+}
+
+impl Config {
+    pub fn is_pinned(&self, project: &str) -> bool {
+        self.pinned.iter().any(|x| x == project)
+    }
+
+    pub fn groups_of(&self, project: &str) -> Vec<&String> {
+        self.groups
+            .iter()
+            .filter(|(_, projects)| projects.iter().any(|x| x == project))
+            .map(|(group, _)| group)
+            .collect()
+    }
+
+    pub fn apply(&mut self, action: ProjectAction) {
+        match action {
+            ProjectAction::TogglePin(project) => {
+                if self.is_pinned(&project) {
+                    self.pinned.retain(|x| *x != project);
+                } else {
+                    self.pinned.push(project);
+                }
+            }
+            ProjectAction::AddToGroup(group, project) => {
+                let projects = self.groups.entry(group).or_default();
+                if !projects.contains(&project) {
+                    projects.push(project);
+                }
+            }
+            ProjectAction::RemoveFromGroup(group, project) => {
+                if let Some(projects) = self.groups.get_mut(&group) {
+                    projects.retain(|x| *x != project);
+                    if projects.is_empty() {
+                        self.groups.remove(&group);
+                    }
+                }
+            }
+        }
+    }
+
+    /// `envs` and `sources` always hold the active workspace,
+    /// the stored copy in `workspaces` is only refreshed when switching away.
+    pub fn switch_workspace(&mut self, name: &str) {
+        if let Some(current) = self.workspaces.iter_mut().find(|w| w.name == self.workspace) {
+            current.envs = self.envs.clone();
+            current.sources = self.sources.clone();
+        }
+        if let Some(next) = self.workspaces.iter().find(|w| w.name == name) {
+            self.envs = next.envs.clone();
+            self.sources = next.sources.clone();
+            self.workspace = name.to_string();
+        }
+    }
+
+    pub fn add_workspace(&mut self, name: &str) {
+        if self.workspaces.iter().any(|w| w.name == name) {
+            return;
+        }
+        if self.workspaces.is_empty() && !self.workspace.is_empty() {
+            self.workspaces.push(Workspace {
+                name: self.workspace.clone(),
+                envs: self.envs.clone(),
+                sources: self.sources.clone(),
+            });
+        }
+        self.workspaces.push(Workspace {
+            name: name.to_string(),
+            envs: self.envs.clone(),
+            sources: self.sources.clone(),
+        });
+        self.switch_workspace(name);
+    }
+
+    pub fn without_secrets(&self) -> Self {
+        let mut config = self.clone();
+        config.gitlab.connection.token = Default::default();
+        config.vault.connection.token = Default::default();
+        config.harbor.connection.password = None;
+        config.harbor.connection.token = None;
+        if let Some(connections) = config.kubernetes.as_mut().and_then(|x| x.connections.as_mut()) {
+            for connection in connections.values_mut() {
+                connection.token = None;
+            }
+        }
+        config
+    }
+
+    pub fn with_secrets_from(mut self, other: &Config) -> Self {
+        if self.gitlab.connection.token.is_empty() {
+            self.gitlab.connection.token = other.gitlab.connection.token.clone();
+        }
+        if self.vault.connection.token.is_empty() {
+            self.vault.connection.token = other.vault.connection.token.clone();
+        }
+        if self.harbor.connection.password.is_none() {
+            self.harbor.connection.password = other.harbor.connection.password.clone();
+        }
+        if self.harbor.connection.token.is_none() {
+            self.harbor.connection.token = other.harbor.connection.token.clone();
+        }
+        let others = other.kubernetes.as_ref().and_then(|x| x.connections.as_ref());
+        if let (Some(connections), Some(others)) = (self.kubernetes.as_mut().and_then(|x| x.connections.as_mut()), others) {
+            for (env, connection) in connections.iter_mut() {
+                if connection.token.is_none() {
+                    connection.token = others.get(env).and_then(|x| x.token.clone());
+                }
+            }
+        }
+        self
+    }
+
+    pub fn export(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(&self.without_secrets()).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    pub fn import(&self, path: &str) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let config = serde_json::from_str::<Config>(&text).map_err(|e| e.to_string())?;
+        Ok(config.with_secrets_from(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::tests::{CONFIG, KUBERNETES};
+
+    fn config() -> super::Config {
+        let mut config: super::Config = serde_yaml::from_str(&format!("{}{}", CONFIG, KUBERNETES)).unwrap();
+        config.gitlab.connection.token = "gitlab-secret".to_string();
+        let connections = config.kubernetes.as_mut().and_then(|x| x.connections.as_mut()).unwrap();
+        connections.get_mut("dev").unwrap().token = Some("cluster-secret".to_string());
+        config
+    }
+
+    #[test]
+    fn export_omits_kubernetes_tokens() {
+        let config = config();
+        let path = std::env::temp_dir().join(format!("deployboard-export-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        config.export(path).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+
+        assert!(!text.contains("cluster-secret"), "{}", text);
+        assert!(!text.contains("gitlab-secret"), "{}", text);
+
+        let imported = config.import(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let connections = imported.kubernetes.and_then(|x| x.connections).unwrap();
+        assert_eq!(connections["dev"].token.as_deref(), Some("cluster-secret"));
+        assert_eq!(imported.gitlab.connection.token, "gitlab-secret");
+    }
+}
@@ -24,7 +24,6 @@ pub async fn get_projects(
         let git_project =
             crate::adapters::gitlab::get_project(&config.gitlab.connection, &source.gitlab_project).await
                 .ok();
-
         let regex_for_name = regex::Regex::new(&config.gitlab.regex_for_name).unwrap();
         let regex_for_env = regex::Regex::new(
             &config
@@ -84,19 +83,34 @@ pub fn fill_deployment(
     let vault_path = deployment.vault_path();
     let project = deployment.source.gitlab_project.clone();
     let path = deployment.path.clone();
+    let workload_name = deployment.workload_name();
+    let env = deployment.env.clone();
 
     let config = config.clone();
     common::execute(async move {
-        if let Ok(raw) = crate::adapters::gitlab::get_filecontent(
+        if let Ok(file) = crate::adapters::gitlab::get_file(
             &config.gitlab.connection,
             &project,
             &path,
             "main",
         ).await {
+            let pipeline = crate::adapters::gitlab::get_latest_pipeline(
+                &config.gitlab.connection,
+                &project,
+                &crate::adapters::gitlab::PipelineQuery {
+                    r#ref: None,
+                    sha: Some(&file.last_commit_id),
+                    per_page: 1,
+                },
+            )
+            .await;
             let mut content = crate::models::DeploymentContent {
-                raw,
+                raw: file.content,
                 secrets: vec![],
                 images: vec![],
+                live: None,
+                fields: vec![],
+                pipeline,
             };
             if let Ok(parsed) = serde_yaml::from_str::<serde_yaml::Value>(&content.raw) {
                 let mut secrets = vec![];
@@ -113,13 +127,14 @@ pub fn fill_deployment(
                 content.secrets = secrets;
 
                 let mut images = vec![];
+                let mut pipelines = PipelineCache::default();
                 for field in crate::yaml::get_fields(
                     &parsed,
                     config.gitlab.image_path.as_str(),
                     Default::default(),
                 )
                 .into_iter(){
-                    if let Some(image) = get_image(field, &config).await {
+                    if let Some(image) = get_image(field, &config, &mut pipelines).await {
                         images.push(image);
                     }
                 }
@@ -136,6 +151,19 @@ pub fn fill_deployment(
                 .filter_map(|field| get_env(field, &config))
                 .collect();
 
+                for field in config.watched_fields.iter() {
+                    for found in crate::yaml::get_fields(&parsed, field.path.as_str(), Default::default()) {
+                        if let Some(value) = crate::yaml::as_string(found.value) {
+                            content.fields.push(crate::models::FieldValue {
+                                field: field.clone(),
+                                source_path: found.path,
+                                edit: value.clone(),
+                                value,
+                            });
+                        }
+                    }
+                }
+
                 for image in content.images.iter_mut() {
                     for env in envs.iter() {
                         if crate::yaml::starts_with_indexonly(&env.source_path, &image.source_path)
@@ -150,6 +178,21 @@ pub fn fill_deployment(
                         .collect();
                     image.envs_json = Some(crate::models::EditorContext::new(envs))
                 }
+
+                if let Some(connection) = config
+                    .kubernetes
+                    .as_ref()
+                    .and_then(|kubernetes| kubernetes.connection(&env))
+                {
+                    content.live = Some(match connection {
+                        Ok(connection) => {
+                            crate::adapters::kubernetes::get_workload(&connection, &workload_name)
+                                .await
+                                .map(|workload| compare_live(&content, &workload))
+                        }
+                        Err(err) => Err(err),
+                    });
+                }
             }
 
             sender.send(content);
@@ -185,6 +228,7 @@ pub async fn get_secret<'a>(
 pub async fn get_image<'a>(
     field: crate::yaml::YamlField<'a>,
     config: &crate::config::Config,
+    pipelines: &mut PipelineCache,
 ) -> Option<crate::models::Image> {
     let image_regex =
         regex::Regex::new(r"(?<domain>[^:/]+)\/(?<project>[^:/]+)\/(?<path>[^:]+):(?<tag>[^:/@]+)")
@@ -223,6 +267,11 @@ pub async fn get_image<'a>(
                     20,
                 ).await
                 .unwrap_or_default();
+                let (pipelines, pipelines_error) =
+                    match get_pipelines(&identifier, &artifact, &artifacts, config, pipelines).await {
+                        Ok(pipelines) => (pipelines, None),
+                        Err(err) => (Default::default(), Some(err)),
+                    };
                 return Some(crate::models::Image {
                     source_path: field.path,
                     identifier,
@@ -230,6 +279,8 @@ pub async fn get_image<'a>(
                     artifacts,
                     envs: vec![],
                     envs_json: Default::default(),
+                    pipelines,
+                    pipelines_error,
                 });
             }
         }
@@ -237,6 +288,58 @@ pub async fn get_image<'a>(
     return None;
 }
 
+/// Recent pipelines by sha per application project, shared by the images of a deployment.
+pub type PipelineCache = std::collections::HashMap<
+    String,
+    Result<std::collections::HashMap<String, crate::adapters::gitlab::Pipeline>, String>,
+>;
+
+/// Pipelines of the application repo keyed by revision, for the deployed and every candidate artifact.
+/// One request for the latest pipelines of the project, revisions older than those have none.
+pub async fn get_pipelines(
+    identifier: &crate::models::ArtifactIdentifier,
+    artifact: &crate::adapters::harbor::Artifact,
+    artifacts: &Vec<crate::adapters::harbor::Artifact>,
+    config: &crate::config::Config,
+    cache: &mut PipelineCache,
+) -> Result<std::collections::HashMap<String, crate::adapters::gitlab::Pipeline>, String> {
+    let Some(project) = config.changelog.projects.get(&identifier.path) else {
+        return Ok(Default::default());
+    };
+    if !cache.contains_key(project) {
+        let by_sha = crate::adapters::gitlab::get_pipelines(
+            &config.gitlab.connection,
+            project,
+            &crate::adapters::gitlab::PipelineQuery {
+                r#ref: None,
+                sha: None,
+                per_page: 100,
+            },
+        )
+        .await
+        .map(|pipelines| {
+            let mut by_sha = std::collections::HashMap::new();
+            // newest first, keep the latest pipeline of each sha
+            for pipeline in pipelines {
+                by_sha.entry(pipeline.sha.clone()).or_insert(pipeline);
+            }
+            by_sha
+        });
+        cache.insert(project.clone(), by_sha);
+    }
+    let by_sha = cache[project].as_ref().map_err(|e| e.clone())?;
+
+    let mut pipelines = std::collections::HashMap::new();
+    for artifact in std::iter::once(artifact).chain(artifacts.iter()) {
+        if let Some(revision) = config.changelog.revision(artifact) {
+            if let Some(pipeline) = by_sha.get(&revision) {
+                pipelines.insert(revision, pipeline.clone());
+            }
+        }
+    }
+    Ok(pipelines)
+}
+
 pub fn get_env<'a>(
     field: crate::yaml::YamlField<'a>,
     config: &crate::config::Config,
@@ -264,3 +367,276 @@ pub fn get_env<'a>(
     return None;
 }
 
+pub fn compare_live(
+    content: &crate::models::DeploymentContent,
+    workload: &crate::adapters::kubernetes::Workload,
+) -> Vec<String> {
+    let mut mismatches = vec![];
+    for image in content.images.iter() {
+        let expected = image
+            .identifier
+            .to_string_with_tag(&image.identifier.tag);
+        let container = workload
+            .containers()
+            .iter()
+            .find(|c| c.image == expected)
+            .or(workload
+                .containers()
+                .iter()
+                .find(|c| c.image.contains(&format!("/{}:", image.identifier.path))));
+        let Some(container) = container else {
+            mismatches.push(format!(
+                "{}: not running in {}",
+                image.identifier.path, workload.kind
+            ));
+            continue;
+        };
+        if container.image != expected {
+            mismatches.push(format!(
+                "{}: live image is {}",
+                image.identifier.path, container.image
+            ));
+        }
+        let live_envs: std::collections::BTreeMap<_, _> = container
+            .env
+            .iter()
+            .map(|e| (e.name.as_str(), e.value.as_deref()))
+            .collect();
+        for env in image.envs.iter() {
+            match live_envs.get(env.name.as_str()) {
+                None => mismatches.push(format!(
+                    "{}: env {} is missing live",
+                    image.identifier.path, env.name
+                )),
+                Some(Some(value)) if *value != env.value => mismatches.push(format!(
+                    "{}: env {} is {} live",
+                    image.identifier.path, env.name, value
+                )),
+                _ => {}
+            }
+        }
+        for (name, _) in live_envs.iter() {
+            if !image.envs.iter().any(|env| env.name == *name) {
+                mismatches.push(format!(
+                    "{}: env {} only exists live",
+                    image.identifier.path, name
+                ));
+            }
+        }
+    }
+    mismatches
+}
+
+/// Commits `content` to main, or to a new branch with a merge request into main when the env requires one.
+/// Returns the merge request url in the latter case.
+pub async fn commit_file(
+    gitlab: &crate::adapters::gitlab::Config,
+    project: &str,
+    path: &str,
+    content: String,
+    last_commit_id: Option<String>,
+    commit_message: &str,
+    require_merge_request: bool,
+) -> Result<Option<String>, String> {
+    let (branch, start_branch) = if require_merge_request {
+        (
+            format!("deployboard/{}", chrono::Utc::now().format("%Y%m%d%H%M%S%3f")),
+            Some("main".to_string()),
+        )
+    } else {
+        ("main".to_string(), None)
+    };
+    crate::adapters::gitlab::create_commit(
+        &gitlab.connection,
+        project,
+        &crate::adapters::gitlab::NewCommit {
+            branch: branch.clone(),
+            start_branch,
+            commit_message: commit_message.to_string(),
+            actions: vec![crate::adapters::gitlab::CommitAction {
+                action: "update".to_string(),
+                file_path: path.to_string(),
+                content,
+                last_commit_id,
+            }],
+            author_email: gitlab.author.email.clone(),
+            author_name: gitlab.author.name.clone(),
+        },
+    )
+    .await?;
+    if !require_merge_request {
+        return Ok(None);
+    }
+    let merge_request = crate::adapters::gitlab::create_merge_request(
+        &gitlab.connection,
+        project,
+        &crate::adapters::gitlab::NewMergeRequest {
+            source_branch: branch,
+            target_branch: "main".to_string(),
+            title: commit_message.to_string(),
+            remove_source_branch: true,
+        },
+    )
+    .await?;
+    Ok(Some(merge_request.web_url))
+}
+
+#[derive(Clone)]
+pub struct FileCommit {
+    pub project: String,
+    pub path: String,
+    pub raw: String,
+    pub new_text: String,
+    /// Set when `raw` was fetched right before, the commit then fails if the file changed since.
+    pub last_commit_id: Option<String>,
+    pub commit_message: String,
+    pub require_merge_request: bool,
+}
+
+/// Commits a deployment file as background job, `description` names the change in the resulting toast.
+pub async fn commit_file_job(
+    gitlab: crate::adapters::gitlab::Config,
+    audit: crate::audit::AuditLog,
+    commit: FileCommit,
+    description: String,
+) -> crate::models::JobResult {
+    let update_result = commit_file(
+        &gitlab,
+        &commit.project,
+        &commit.path,
+        commit.new_text.clone(),
+        commit.last_commit_id.clone(),
+        &commit.commit_message,
+        commit.require_merge_request,
+    )
+    .await;
+    let warnings = match audit.record(
+        crate::audit::AuditKind::Gitlab,
+        &format!("{}/{}", commit.project, commit.path),
+        &commit.raw,
+        &commit.new_text,
+        &update_result,
+    ) {
+        Ok(()) => vec![],
+        Err(err) => vec![format!("Error writing audit log for {}:\n {}", description, err)],
+    };
+    let result = match update_result {
+        Err(err) => crate::models::JobResult::error(format!("Error Updating {}:\n {}", description, err)),
+        Ok(Some(merge_request)) => crate::models::JobResult::success(
+            format!("Merge request for {} created:\n {}", description, merge_request),
+            false,
+        ),
+        Ok(None) => crate::models::JobResult::success(format!("Updating {} succeed!", description), true),
+    };
+    result.with_warnings(warnings)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    pub const CONFIG: &str = r#"
+gitlab:
+  connection: { endpoint: "https://gitlab.test", token: "" }
+  author: { email: "board@test", name: "board" }
+  regex_for_name: "^(?<name>[^/]+)/"
+  regex_for_env: "values-(?<env>[a-z]+)\\.yaml$"
+  secret_path: secret
+  image_path: image
+  envs_path: env
+  env_name_path: name
+  env_value_path: value
+vault:
+  connection: { endpoint: "https://vault.test", token: "" }
+harbor:
+  connection: { endpoint: "https://harbor.test" }
+  project_name: apps
+  project_id: 1
+envs: [dev]
+sources:
+  - gitlab_project: "42"
+    vault_path: apps
+    workloads: { shop: shop-api }
+changelog:
+  projects: { shop: "7" }
+"#;
+
+    pub const KUBERNETES: &str = r#"
+kubernetes:
+  contexts: {}
+  connections:
+    dev: { endpoint: "https://k8s.test", namespace: shop }
+"#;
+
+    /// Answers from the fixtures checked in next to the manifest.
+    pub fn replay(config: &str) -> crate::config::Config {
+        crate::adapters::http::set_mode(crate::adapters::http::HttpMode::Replay {
+            fixtures: concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures").to_string(),
+        });
+        serde_yaml::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn get_projects_from_fixtures() {
+        let config = replay(CONFIG);
+        let projects = futures::executor::block_on(super::get_projects(&config, egui::Context::default()));
+
+        assert_eq!(projects.keys().collect::<Vec<_>>(), vec!["shop"]);
+        let deployment = &projects["shop"].deployments_by_env["dev"];
+        assert_eq!(deployment.path, "shop/values-dev.yaml");
+        assert_eq!(deployment.vault_path().as_deref(), Some("apps"));
+        assert_eq!(
+            deployment.git_project.as_ref().map(|x| x.web_url.as_str()),
+            Some("https://gitlab.test/ops/deployments")
+        );
+    }
+
+    #[test]
+    fn fill_deployment_from_fixtures() {
+        let config = replay(CONFIG);
+        let mut projects = futures::executor::block_on(super::get_projects(&config, egui::Context::default()));
+        let deployment = projects.get_mut("shop").unwrap().deployments_by_env.get_mut("dev").unwrap();
+
+        super::fill_deployment(deployment, &config, egui::Context::default());
+        let content = deployment.content.take().unwrap().block_and_take();
+
+        assert!(content.raw.starts_with("image: registry.test/apps/shop:1.2.0"));
+        assert_eq!(content.pipeline.as_ref().unwrap().as_ref().map(|x| x.id), Some(100));
+
+        assert_eq!(content.secrets.len(), 1);
+        assert_eq!(content.secrets[0].vault_name, "shop");
+        assert_eq!(
+            content.secrets[0].secrets.orignal_data.get("DB_PASSWORD").map(|x| x.as_str()),
+            Some(crate::adapters::http::REDACTED)
+        );
+
+        assert_eq!(content.images.len(), 1);
+        let image = &content.images[0];
+        assert_eq!(image.identifier.tag, "1.2.0");
+        assert_eq!(
+            image.envs.iter().map(|x| (x.name.as_str(), x.value.as_str())).collect::<Vec<_>>(),
+            vec![("LOG_LEVEL", "info"), ("REPLICAS", "2")]
+        );
+        assert!(image.pipelines_error.is_none());
+        // the latest pipeline of a sha wins
+        assert_eq!(image.pipeline(&image.artifact, &config.changelog).map(|x| x.id), Some(201));
+        assert_eq!(image.pipeline(&image.artifacts[0], &config.changelog).map(|x| x.id), Some(202));
+    }
+
+    #[test]
+    fn compare_live_from_fixtures() {
+        let config = replay(&format!("{}{}", CONFIG, KUBERNETES));
+        let mut projects = futures::executor::block_on(super::get_projects(&config, egui::Context::default()));
+        let deployment = projects.get_mut("shop").unwrap().deployments_by_env.get_mut("dev").unwrap();
+        assert_eq!(deployment.workload_name(), "shop-api");
+
+        super::fill_deployment(deployment, &config, egui::Context::default());
+        let content = deployment.content.take().unwrap().block_and_take();
+
+        assert_eq!(
+            content.live,
+            Some(Ok(vec![
+                "shop: env LOG_LEVEL is debug live".to_string(),
+                "shop: env DB_PASSWORD only exists live".to_string(),
+            ]))
+        );
+    }
+}
@@ -3,15 +3,25 @@ mod adapters {
     pub mod gitlab;
     pub mod harbor;
     pub mod http;
+    pub mod kubernetes;
     pub mod vault;
 }
+mod audit;
+mod changelog;
+mod changes;
 mod config;
 mod core;
 mod models;
+mod protection;
+mod search;
 mod ui;
+mod ui_audit;
+mod ui_bulk_env;
 mod ui_save_env;
+mod ui_save_field;
 mod ui_save_vault;
 mod ui_settings;
+mod watch;
 mod yaml;
 
 use common::RemoveWhere;
@@ -34,8 +44,23 @@ pub struct App {
         poll_promise::Promise<std::collections::BTreeMap<String, crate::models::DeployProject>>,
     pub env_settings: Vec<bool>,
     pub search: String,
+    pub group: Option<String>,
+    pub new_group: String,
+    pub config_file: String,
+    pub new_workspace: String,
     pub modals: Vec<crate::models::Modal>,
     pub toasts: egui_notify::Toasts,
+    pub jobs: crate::models::Jobs,
+    pub changes: crate::changes::ChangeSet,
+    pub audit: crate::audit::AuditLog,
+    pub show_audit: bool,
+    pub audit_entries: Vec<crate::audit::AuditEntry>,
+    pub audit_filter: String,
+    pub audit_file: String,
+    pub audit_errors_only: bool,
+    pub show_bulk_env: bool,
+    pub bulk_env: crate::ui_bulk_env::BulkEnv,
+    pub watcher: crate::watch::Watcher,
     pub egui_ctx: egui::Context,
 }
 
@@ -56,9 +81,24 @@ impl App {
             project_by_name: promise,
             env_settings: vec![],
             search: String::default(),
+            group: None,
+            new_group: String::default(),
+            config_file: "deployboard.json".to_string(),
+            new_workspace: String::default(),
             modals: vec![],
             toasts: egui_notify::Toasts::default(),
+            jobs: crate::models::Jobs::new(cc.egui_ctx.clone()),
+            changes: Default::default(),
             show_settings: false,
+            audit: Default::default(),
+            show_audit: false,
+            audit_entries: vec![],
+            audit_filter: String::default(),
+            audit_file: "deployboard-audit.json".to_string(),
+            audit_errors_only: false,
+            show_bulk_env: false,
+            bulk_env: Default::default(),
+            watcher: Default::default(),
             egui_ctx: cc.egui_ctx.clone(),
         };
 
@@ -67,7 +107,20 @@ impl App {
     }
 
     pub fn reload(&mut self, vault: bool) {
-        if vault {
+        crate::adapters::http::set_mode(self.config.http.clone());
+        crate::adapters::http::set_proxy(
+            self.config.proxy.as_deref(),
+            &[
+                (&self.config.gitlab.connection.endpoint, "gitlab"),
+                (&self.config.vault.connection.endpoint, "vault"),
+                (&self.config.harbor.connection.endpoint, "harbor"),
+            ],
+        );
+        // with a proxy the vault token is held by the proxy
+        if vault
+            && self.config.proxy.is_none()
+            && !matches!(self.config.http, crate::adapters::http::HttpMode::Replay { .. })
+        {
             if let Ok(token) =
                 crate::adapters::vault::get_token(&self.config.vault.connection.endpoint)
             {
@@ -75,6 +128,7 @@ impl App {
                 *t = token;
             }
         }
+        self.audit = crate::audit::AuditLog::new(&self.config);
         let config = self.config.clone();
         let (sender, promise) = poll_promise::Promise::new();
         let ctx = self.egui_ctx.clone();
@@ -93,14 +147,38 @@ impl App {
         // }
     }
 
+    pub fn open_audit(&mut self) {
+        match self.audit.read() {
+            Ok(entries) => self.audit_entries = entries,
+            Err(err) => {
+                self.toasts.error(format!("Error reading audit log:\n {}", err));
+            }
+        }
+        self.show_audit = true;
+    }
+
     pub fn update(&mut self, mut ctx: common::app::Context<'_>) {
         crate::ui_settings::show_settings(self, &mut ctx);
+        crate::ui_audit::show_audit(self, &mut ctx);
+        crate::ui_bulk_env::show_bulk_env(self, &mut ctx);
+        crate::watch::show_notifications(self, &mut ctx);
+        crate::changes::show_changes(self, &mut ctx);
 
         self.egui_ctx = ctx.ui.ctx().clone();
 
+        self.watch(&ctx.ui.ctx().clone());
+
         
 
         let mut show_settings = false;
+        let mut show_audit = false;
+        let mut show_bulk_env = false;
+        let mut show_notifications = false;
+        let mut show_changes = false;
+        let notification_count = self.watcher.events.len();
+        let change_count = self.changes.changes.len();
+        let applying = self.jobs.is_running();
+        let mut switch_workspace = None;
         egui::Sides::new().show(
             ctx.ui,
             |ui| {
@@ -108,6 +186,23 @@ impl App {
                     if ui.button("⟳").clicked() {
                         self.reload(false);
                     }
+                    if !self.config.workspaces.is_empty() {
+                        egui::ComboBox::from_id_salt("workspace")
+                            .selected_text(&self.config.workspace)
+                            .show_ui(ui, |ui| {
+                                for workspace in self.config.workspaces.iter() {
+                                    if ui
+                                        .selectable_label(
+                                            workspace.name == self.config.workspace,
+                                            &workspace.name,
+                                        )
+                                        .clicked()
+                                    {
+                                        switch_workspace = Some(workspace.name.clone());
+                                    }
+                                }
+                            });
+                    }
                     for (i, enabled) in self.env_settings.iter_mut().enumerate() {
                         if ui
                             .selectable_label(*enabled, &self.config.envs[i])
@@ -128,12 +223,42 @@ impl App {
                     if !self.search.is_empty() && ui.button("x").clicked() {
                         self.search = Default::default();
                     }
+                    if !self.config.groups.is_empty() {
+                        egui::ComboBox::from_id_salt("group")
+                            .selected_text(self.group.as_deref().unwrap_or("all groups"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.group, None, "all groups");
+                                for group in self.config.groups.keys() {
+                                    ui.selectable_value(&mut self.group, Some(group.clone()), group);
+                                }
+                            });
+                    }
                 });
             },
             |ui| {
                 if ui.button("⚙").clicked() {
                     show_settings = true;
                 }
+                if ui.button("audit").clicked() {
+                    show_audit = true;
+                }
+                if ui.button("bulk env").clicked() {
+                    show_bulk_env = true;
+                }
+                if ui
+                    .button(format!("notifications ({})", notification_count))
+                    .clicked()
+                {
+                    show_notifications = true;
+                }
+                let label = if change_count > 0 {
+                    RichText::new(format!("changes ({})", change_count)).strong()
+                } else {
+                    RichText::new("changes")
+                };
+                if ui.add_enabled(!applying, egui::Button::new(label)).clicked() {
+                    show_changes = true;
+                }
             },
         );
 
@@ -141,6 +266,30 @@ impl App {
             self.show_settings = true;
         }
 
+        if show_audit {
+            self.open_audit();
+        }
+
+        if show_bulk_env {
+            self.show_bulk_env = true;
+        }
+
+        if show_notifications {
+            self.watcher.show = true;
+        }
+
+        if show_changes {
+            self.changes.show = true;
+        }
+
+        if let Some(name) = switch_workspace {
+            let mut config = self.config.orignal_data.clone();
+            config.switch_workspace(&name);
+            self.config.set(config);
+            ctx.save(&self.config.orignal_data);
+            self.reload(false);
+        }
+
         if !self.env_settings.is_empty() {
             let envs: Vec<_> = self
                 .config
@@ -155,9 +304,19 @@ impl App {
                     ui.label(RichText::new(env).strong());
                 }
             });
+            let mut actions = vec![];
             egui::ScrollArea::vertical().show(ctx.ui, |ui| {
                 if let Some(project_by_name) = self.project_by_name.ready_mut() {
-                    for (_, project) in project_by_name.iter_mut() {
+                    let mut projects: Vec<_> = project_by_name
+                        .values_mut()
+                        .filter(|project| {
+                            self.group.as_ref().is_none_or(|group| {
+                                self.config.groups_of(&project.name).contains(&group)
+                            })
+                        })
+                        .collect();
+                    projects.sort_by_key(|project| !self.config.is_pinned(&project.name));
+                    for project in projects {
                         crate::ui::show_project(
                             project,
                             &self.config,
@@ -165,6 +324,8 @@ impl App {
                             &envs,
                             &self.search,
                             &mut self.modals,
+                            &mut actions,
+                            &mut self.new_group,
                         );
                     }
                 } else {
@@ -174,6 +335,15 @@ impl App {
                     });
                 }
             });
+
+            if !actions.is_empty() {
+                let mut config = self.config.orignal_data.clone();
+                for action in actions {
+                    config.apply(action);
+                }
+                self.config.set(config);
+                ctx.save(&self.config.orignal_data);
+            }
         }
 
         let mut reload = false;
@@ -183,6 +353,8 @@ impl App {
                 close: false,
                 reload: false,
                 toasts: &mut self.toasts,
+                audit: &self.audit,
+                changes: &mut self.changes,
             };
             let resp = egui::Modal::new(egui::Id::new(&modal.id)).show(egui_ctx, |ui| {
                 (modal.ui)(ui, &mut m_ctx);
@@ -196,6 +368,25 @@ impl App {
             return false;
         });
 
+        self.jobs.running.remove_where(|job| {
+            if let Some(result) = job.ready() {
+                match &result.message {
+                    Ok(message) => {
+                        self.toasts.success(message.clone());
+                    }
+                    Err(message) => {
+                        self.toasts.error(message.clone());
+                    }
+                }
+                for warning in result.warnings.iter() {
+                    self.toasts.warning(warning.clone());
+                }
+                reload |= result.reload;
+                return true;
+            }
+            return false;
+        });
+
         if reload {
             self.reload(false);
         }
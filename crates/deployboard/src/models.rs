@@ -44,6 +44,14 @@ impl Deployment {
         return None;
     }
 
+    pub fn workload_name(&self) -> String {
+        self.source
+            .workloads
+            .as_ref()
+            .and_then(|x| x.get(&self.name))
+            .cloned()
+            .unwrap_or_else(|| self.name.to_lowercase())
+    }
 }
 
 #[derive(Clone)]
@@ -51,6 +59,18 @@ pub struct DeploymentContent {
     pub raw: String,
     pub secrets: Vec<Secret>,
     pub images: Vec<Image>,
+    pub live: Option<Result<Vec<String>, String>>,
+    pub fields: Vec<FieldValue>,
+    /// Pipeline of the commit that last changed the file.
+    pub pipeline: Result<Option<crate::adapters::gitlab::Pipeline>, String>,
+}
+
+#[derive(Clone)]
+pub struct FieldValue {
+    pub field: crate::config::WatchedField,
+    pub source_path: crate::yaml::Path,
+    pub value: String,
+    pub edit: String,
 }
 #[derive(Clone)]
 pub struct Secret {
@@ -83,6 +103,13 @@ impl<T :  serde::Serialize> EditorContext<T> {
         self
     }
 
+    pub fn set(&mut self, data: T) {
+        *self = Self {
+            always_saveable: self.always_saveable,
+            ..Self::new(data)
+        };
+    }
+
     pub fn reset(&mut self) {
         self.text = self.orignal_text.clone();
     }
@@ -128,6 +155,20 @@ pub struct Image {
     pub identifier: ArtifactIdentifier,
     pub envs: Vec<EnvVar>,
     pub envs_json: Option<EditorContext<std::collections::BTreeMap<String, String>>>,
+    pub pipelines: std::collections::HashMap<String, crate::adapters::gitlab::Pipeline>,
+    pub pipelines_error: Option<String>,
+}
+
+impl Image {
+    pub fn pipeline(
+        &self,
+        artifact: &crate::adapters::harbor::Artifact,
+        changelog: &crate::changelog::ChangelogConfig,
+    ) -> Option<&crate::adapters::gitlab::Pipeline> {
+        changelog
+            .revision(artifact)
+            .and_then(|revision| self.pipelines.get(&revision))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -153,6 +194,80 @@ pub struct ModalContext<'a> {
     pub close: bool,
     pub reload: bool,
     pub toasts: &'a mut egui_notify::Toasts,
+    pub audit: &'a crate::audit::AuditLog,
+    pub changes: &'a mut crate::changes::ChangeSet,
+}
+
+/// Outcome of a background write, shown as toast once it is done.
+pub struct JobResult {
+    pub message: Result<String, String>,
+    pub reload: bool,
+    /// Problems that did not stop the write, e.g. a failed audit log entry.
+    pub warnings: Vec<String>,
+}
+
+impl JobResult {
+    pub fn success(message: impl Into<String>, reload: bool) -> Self {
+        Self {
+            message: Ok(message.into()),
+            reload,
+            warnings: vec![],
+        }
+    }
+
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            message: Err(message.into()),
+            reload: false,
+            warnings: vec![],
+        }
+    }
+}
+
+/// Writes run as jobs so they never block the ui and also work on the web, where blocking requests are impossible.
+pub struct Jobs {
+    pub running: Vec<poll_promise::Promise<JobResult>>,
+    pub egui_ctx: egui::Context,
+}
+
+impl Jobs {
+    pub fn new(egui_ctx: egui::Context) -> Self {
+        Self {
+            running: vec![],
+            egui_ctx,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(&mut self, job: impl std::future::Future<Output = JobResult> + Send + 'static) {
+        let (sender, promise) = poll_promise::Promise::new();
+        let ctx = self.egui_ctx.clone();
+        common::execute(async move {
+            sender.send(job.await);
+            ctx.request_repaint();
+        });
+        self.running.push(promise);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn(&mut self, job: impl std::future::Future<Output = JobResult> + 'static) {
+        let (sender, promise) = poll_promise::Promise::new();
+        let ctx = self.egui_ctx.clone();
+        common::execute(async move {
+            sender.send(job.await);
+            ctx.request_repaint();
+        });
+        self.running.push(promise);
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
 }
 
 impl Modal {
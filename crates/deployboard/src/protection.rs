@@ -0,0 +1,188 @@
+use egui::Widget;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct ProtectionConfig {
+    pub freezes: Vec<Freeze>,
+    pub envs: std::collections::HashMap<String, Protection>,
+}
+
+/// A release freeze, without `envs` it applies to every env.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Freeze {
+    pub name: String,
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub end: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub envs: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct Protection {
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub confirm_env_name: bool,
+    #[serde(default)]
+    pub require_merge_request: bool,
+}
+
+impl ProtectionConfig {
+    pub fn protection(&self, env: &str) -> Protection {
+        self.envs.get(env).cloned().unwrap_or_default()
+    }
+
+    pub fn active_freeze(&self, env: &str, now: chrono::DateTime<chrono::Utc>) -> Option<&Freeze> {
+        self.freezes.iter().find(|freeze| {
+            freeze.start <= now
+                && now < freeze.end
+                && (freeze.envs.is_empty() || freeze.envs.iter().any(|x| x == env))
+        })
+    }
+
+    /// Why writing to `env` is not possible right now, if it is not.
+    pub fn blocked(&self, env: &str) -> Option<String> {
+        if self.protection(env).read_only {
+            return Some(format!("{} is read-only", env));
+        }
+        if let Some(freeze) = self.active_freeze(env, chrono::Utc::now()) {
+            return Some(format!(
+                "{} is frozen by {} until {}",
+                env,
+                freeze.name,
+                freeze.end.format("%d.%m.%Y %H:%M")
+            ));
+        }
+        None
+    }
+
+    /// Whether writes to `env` may happen without anyone confirming them, e.g. from the watcher.
+    pub fn unattended(&self, env: &str) -> bool {
+        let protection = self.protection(env);
+        self.blocked(env).is_none() && !protection.confirm_env_name && !protection.require_merge_request
+    }
+}
+
+/// Held by a save modal, the save button is only enabled while `allowed` is true.
+#[derive(Clone, Default)]
+pub struct Guard {
+    pub config: ProtectionConfig,
+    pub env: String,
+    pub confirmation: String,
+    /// Vault has no merge requests, writes to envs that require one are blocked.
+    pub vault: bool,
+}
+
+impl Guard {
+    pub fn new(config: &ProtectionConfig, env: &str) -> Self {
+        Self {
+            config: config.clone(),
+            env: env.to_string(),
+            confirmation: String::default(),
+            vault: false,
+        }
+    }
+
+    pub fn for_vault(config: &ProtectionConfig, env: &str) -> Self {
+        Self {
+            vault: true,
+            ..Self::new(config, env)
+        }
+    }
+
+    fn blocked(&self) -> Option<String> {
+        if self.vault && self.require_merge_request() {
+            return Some(format!(
+                "{} requires a merge request, vault secrets can't be changed from here",
+                self.env
+            ));
+        }
+        self.config.blocked(&self.env)
+    }
+
+    pub fn require_merge_request(&self) -> bool {
+        self.config.protection(&self.env).require_merge_request
+    }
+
+    pub fn allowed(&self) -> bool {
+        self.blocked().is_none()
+            && (!self.config.protection(&self.env).confirm_env_name || self.confirmation == self.env)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        if let Some(reason) = self.blocked() {
+            ui.colored_label(ui.visuals().error_fg_color, reason);
+            return;
+        }
+        if self.config.protection(&self.env).confirm_env_name {
+            ui.horizontal(|ui| {
+                ui.label(format!("type {} to confirm ", self.env));
+                egui::TextEdit::singleline(&mut self.confirmation).ui(ui);
+            });
+        }
+        if self.require_merge_request() {
+            ui.label(format!("changes to {} are proposed as merge request", self.env));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+freezes:
+  - { name: release, start: "2024-12-20T00:00:00Z", end: "2025-01-06T00:00:00Z", envs: [prod] }
+  - { name: migration, start: "2024-11-01T08:00:00Z", end: "2024-11-01T12:00:00Z" }
+envs:
+  prod: { confirm_env_name: true }
+  staging: { require_merge_request: true }
+  demo: { read_only: true }
+"#;
+
+    fn config() -> ProtectionConfig {
+        serde_yaml::from_str(CONFIG).unwrap()
+    }
+
+    fn at(text: &str) -> chrono::DateTime<chrono::Utc> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn freeze_starts_inclusive_and_ends_exclusive() {
+        let config = config();
+        assert!(config.active_freeze("prod", at("2024-12-19T23:59:59Z")).is_none());
+        assert_eq!(config.active_freeze("prod", at("2024-12-20T00:00:00Z")).unwrap().name, "release");
+        assert_eq!(config.active_freeze("prod", at("2025-01-05T23:59:59Z")).unwrap().name, "release");
+        assert!(config.active_freeze("prod", at("2025-01-06T00:00:00Z")).is_none());
+    }
+
+    #[test]
+    fn freeze_with_envs_only_applies_to_them() {
+        let config = config();
+        assert!(config.active_freeze("dev", at("2024-12-24T00:00:00Z")).is_none());
+        // without envs the freeze applies everywhere
+        for env in ["dev", "prod", "unknown"] {
+            assert_eq!(config.active_freeze(env, at("2024-11-01T10:00:00Z")).unwrap().name, "migration");
+        }
+    }
+
+    #[test]
+    fn unattended_only_without_confirmation() {
+        let mut config = config();
+        config.freezes.clear();
+        assert!(config.unattended("dev"));
+        assert!(!config.unattended("prod"));
+        assert!(!config.unattended("staging"));
+        assert!(!config.unattended("demo"));
+
+        let now = chrono::Utc::now();
+        config.freezes.push(Freeze {
+            name: "now".to_string(),
+            start: now - chrono::Duration::hours(1),
+            end: now + chrono::Duration::hours(1),
+            envs: vec!["dev".to_string()],
+        });
+        assert!(!config.unattended("dev"));
+        assert!(config.blocked("dev").unwrap().starts_with("dev is frozen by now until"));
+    }
+}
@@ -0,0 +1,58 @@
+/// Case-insensitive subsequence match, so "usrsvc" finds "user-service".
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| text.any(|t| t == q))
+}
+
+/// Case-insensitive substring match, for names where a subsequence would hit almost anything.
+pub fn substring_match(query: &str, text: &str) -> bool {
+    text.to_lowercase().contains(&query.to_lowercase())
+}
+
+/// Every term has to match, fuzzy on the project name and as a substring on everything else.
+pub fn project_matches(
+    project: &crate::models::DeployProject,
+    config: &crate::config::Config,
+    search: &str,
+) -> bool {
+    let haystack = haystack(project, config);
+    search.split_whitespace().all(|term| {
+        fuzzy_match(term, &project.name) || haystack.iter().any(|text| substring_match(term, text))
+    })
+}
+
+/// Everything but the name a project can be found by, image paths, tags and env names only once its content is loaded.
+pub fn haystack(
+    project: &crate::models::DeployProject,
+    config: &crate::config::Config,
+) -> Vec<String> {
+    let mut haystack: Vec<String> = config.groups_of(&project.name).into_iter().cloned().collect();
+    for deployment in project.deployments_by_env.values() {
+        if let Some(content) = deployment.content.as_ref().and_then(|x| x.ready()) {
+            for image in content.images.iter() {
+                haystack.push(image.identifier.path.clone());
+                haystack.extend(image.artifact.tags.iter().map(|x| x.name.clone()));
+                haystack.extend(image.envs.iter().map(|x| x.name.clone()));
+            }
+        }
+    }
+    haystack
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_only_for_project_names() {
+        assert!(fuzzy_match("usrsvc", "user-service"));
+        assert!(!fuzzy_match("svcusr", "user-service"));
+        assert!(substring_match("DATABASE", "DATABASE_URL"));
+        assert!(substring_match("url", "DATABASE_URL"));
+        // a subsequence of most env var names
+        assert!(!substring_match("dbl", "DATABASE_URL"));
+    }
+}
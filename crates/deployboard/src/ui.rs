@@ -1,17 +1,21 @@
 use egui::{Ui, Widget};
 
-use crate::{adapters::gitlab::FileUpdate, models::{EditorContext, ModalContext}, ui_save_vault::show};
+use crate::{models::{EditorContext, ModalContext}, ui_save_vault::show};
 
 pub fn show_project(
     project: &mut crate::models::DeployProject,
     config: &crate::config::Config,
     ui: &mut Ui,
     envs: &Vec<&String>,
-    search: &str, modals : &mut Vec<crate::models::Modal>
+    search: &str, modals : &mut Vec<crate::models::Modal>,
+    actions: &mut Vec<crate::config::ProjectAction>,
+    new_group: &mut String,
 ) {
-    if !search.is_empty() && !project.name.contains(search) {
+    if !search.is_empty() && !crate::search::project_matches(project, config, search) {
         return;
     }
+    let pinned = config.is_pinned(&project.name);
+    let groups = config.groups_of(&project.name);
     ui.columns(envs.len(), |columns: &mut [Ui]| {
         for (i, &env) in envs.iter().enumerate() {
             let ui = &mut columns[i];
@@ -25,7 +29,31 @@ pub fn show_project(
                     {
                         project.details_open = !project.details_open;
                     }
-                    ui.label(&project.name);
+                    if ui.selectable_label(pinned, if pinned { "★" } else { "☆" }).clicked() {
+                        actions.push(crate::config::ProjectAction::TogglePin(project.name.clone()));
+                    }
+                    ui.label(&project.name).context_menu(|ui| {
+                        for (group, _) in config.groups.iter() {
+                            let mut member = groups.contains(&group);
+                            if ui.checkbox(&mut member, group).changed() {
+                                actions.push(if member {
+                                    crate::config::ProjectAction::AddToGroup(group.clone(), project.name.clone())
+                                } else {
+                                    crate::config::ProjectAction::RemoveFromGroup(group.clone(), project.name.clone())
+                                });
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            egui::TextEdit::singleline(new_group).hint_text("new group").ui(ui);
+                            if ui.add_enabled(!new_group.is_empty(), egui::Button::new("+")).clicked() {
+                                actions.push(crate::config::ProjectAction::AddToGroup(std::mem::take(new_group), project.name.clone()));
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    for group in groups.iter() {
+                        ui.small(group.as_str());
+                    }
                     if let Some(git_project) = &deployment.git_project {
                         ui.hyperlink_to(
                             "src",
@@ -38,6 +66,13 @@ pub fn show_project(
                             &format!("{}/applications/argocd/{}{}", endpoint, deployment.argocd_prefix().unwrap_or_default(), deployment.name.to_lowercase()),
                         );
                     }
+                    match deployment.content.as_ref().and_then(|x| x.ready()).map(|x| &x.pipeline) {
+                        Some(Ok(Some(pipeline))) => show_pipeline(ui, pipeline),
+                        Some(Err(err)) => {
+                            ui.colored_label(ui.visuals().error_fg_color, "pipeline").on_hover_text(err);
+                        }
+                        _ => {}
+                    }
                 });
 
                 if project.details_open {
@@ -51,6 +86,20 @@ pub fn show_project(
                                 if content.images.is_empty() && content.secrets.is_empty() {
                                     ui.label("no images / sercets found");
                                 }
+                                match &content.live {
+                                    Some(Ok(mismatches)) if mismatches.is_empty() => {
+                                        ui.label("live: in sync");
+                                    }
+                                    Some(Ok(mismatches)) => {
+                                        for mismatch in mismatches.iter() {
+                                            ui.colored_label(ui.visuals().warn_fg_color, format!("live: {}", mismatch));
+                                        }
+                                    }
+                                    Some(Err(err)) => {
+                                        ui.colored_label(ui.visuals().error_fg_color, format!("live: {}", err));
+                                    }
+                                    None => {}
+                                }
                                 for image in content.images.iter_mut() {
                                     
                                     let tags : Vec<_> = image.artifact.tags.iter().map(|x|x.name.as_str()).collect();
@@ -59,6 +108,12 @@ pub fn show_project(
                                             "{}: {}",
                                             image.identifier.path, tags.join(" | ")
                                         ));
+                                        if let Some(pipeline) = image.pipeline(&image.artifact, &config.changelog) {
+                                            show_pipeline(ui, pipeline);
+                                        }
+                                        if let Some(err) = &image.pipelines_error {
+                                            ui.colored_label(ui.visuals().error_fg_color, "pipeline").on_hover_text(err);
+                                        }
                                         resp.context_menu(|ui|{
                                             ui.hyperlink_to(
                                                 "src",
@@ -68,10 +123,18 @@ pub fn show_project(
                                             ui.menu_button("change to image", |ui| {
                                                 for artifact in image.artifacts.iter() {
                                                     let tags : Vec<_> = artifact.tags.iter().map(|x|x.name.as_str()).collect();
-                                                    if ui.button(format!(
-                                                        "{}", tags.join(" | ")
-                                                    )).clicked() {
-                                                        let gitlab = config.gitlab.clone();
+                                                    let pipeline = image.pipeline(artifact, &config.changelog).cloned();
+                                                    let clicked = ui.horizontal(|ui| {
+                                                        let clicked = ui.button(format!(
+                                                            "{}", tags.join(" | ")
+                                                        )).clicked();
+                                                        if let Some(pipeline) = &pipeline {
+                                                            show_pipeline(ui, pipeline);
+                                                        }
+                                                        clicked
+                                                    }).inner;
+                                                    if clicked {
+                                                        let old_pipeline = image.pipeline(&image.artifact, &config.changelog).cloned();
                                                         let deployment_name = deployment.name.clone();
                                                         let deployment_env = deployment.env.clone();
                                                         let image = image.clone();
@@ -80,6 +143,8 @@ pub fn show_project(
                                                         let project = deployment.source.gitlab_project.clone();
                                                         let path = deployment.path.clone();
                                                         let mut commit_message = format!("{} {}: update image to {}", deployment_env, deployment_name, new_artifact.tags[0].name);
+                                                        let changelog = crate::changelog::get_changelog(config, &image.identifier.path, &image.artifact, &new_artifact, ui.ctx().clone());
+                                                        let mut guard = crate::protection::Guard::new(&config.protection, &deployment_env);
                                                         modals.push(crate::models::Modal::new(format!("{}:{}->{}", deployment.name, artifact.tags[0].name, image.artifact.tags[0].name), move |ui: &mut Ui, ctx: &mut ModalContext| {
                                                             
                                                             ui.set_width(750.0);
@@ -93,32 +158,39 @@ pub fn show_project(
                                                             ui.columns(2, |columns: &mut [Ui]| {
                                                                 show_artifact(&mut columns[0], &image.artifact, "old");
                                                                 show_artifact(&mut columns[1], &new_artifact, "new");
+                                                                if let Some(pipeline) = &old_pipeline {
+                                                                    show_pipeline(&mut columns[0], pipeline);
+                                                                }
+                                                                if let Some(pipeline) = &pipeline {
+                                                                    show_pipeline(&mut columns[1], pipeline);
+                                                                }
                                                             });
+                                                            if pipeline.as_ref().is_some_and(|x| x.failed()) {
+                                                                ui.colored_label(ui.visuals().error_fg_color, "the pipeline of the new image failed");
+                                                            }
+                                                            crate::changelog::show_changelog(ui, &changelog);
                                                             
                                                             ui.separator();
+                                                            guard.show(ui);
                                                             egui::Sides::new().show(
                                                                 ui,
                                                                 |_ui| {},
                                                                 |ui| {
-                                                                    if ui.button("Save").clicked() {
-                                                                        let mut yaml = serde_yaml::from_str::<serde_yaml::Value>(&raw).unwrap();
-
+                                                                    if ui.add_enabled(guard.allowed(), egui::Button::new("Stage")).clicked() {
                                                                         let new_image = image.identifier.to_string_with_tag(&new_artifact.tags[0].name);
-                                                                        crate::yaml::set_field(&mut yaml, &image.source_path, &serde_yaml::Value::String(new_image.clone()), false);
-                                                                        let new_text = serde_yaml::to_string(&yaml).unwrap();
                                                                         ctx.close = true;
-                                                                        let update_result = crate::adapters::gitlab::update_file(&gitlab.connection, &project, &path, &FileUpdate{ 
-                                                                            branch: "main".to_string(), 
-                                                                            commit_message: commit_message.clone(), 
-                                                                            content: new_text, 
-                                                                            author_email: gitlab.author.email.clone(), 
-                                                                            author_name: gitlab.author.name.clone() });
-                                                                        if let Err(err) = update_result {
-                                                                            ctx.toasts.error(format!("Error Updating {} to {}:\n {}",  deployment_name, new_image, err));
-                                                                        } else {
-                                                                            ctx.toasts.success(format!("Updating {} to {} succeed!", deployment_name, new_image));
-                                                                            ctx.reload = true;
-                                                                        }
+                                                                        ctx.changes.stage(crate::changes::Change {
+                                                                            description: format!("{} to {}", deployment_name, new_image),
+                                                                            env: deployment_env.clone(),
+                                                                            commit_message: commit_message.clone(),
+                                                                            require_merge_request: guard.require_merge_request(),
+                                                                            kind: crate::changes::ChangeKind::File {
+                                                                                project: project.clone(),
+                                                                                path: path.clone(),
+                                                                                raw: raw.clone(),
+                                                                                edits: vec![(image.source_path.clone(), serde_yaml::Value::String(new_image))],
+                                                                            },
+                                                                        });
                                                                     }
                                                                     if ui.button("Cancel").clicked() {
                                                                         ctx.close = true;
@@ -136,13 +208,17 @@ pub fn show_project(
                                         show_text(ui, ui.next_auto_id(), |ui|{
                                             
                                         },envs_json, |orginal,new|{
-                                            modals.push(crate::ui_save_env::show(config.gitlab.clone(), orginal, new, deployment.source.gitlab_project.clone(), deployment.path.clone(), content.raw.clone(), image.identifier.path.clone(), image.source_path.clone(), deployment.env.clone(), deployment.name.clone()));
+                                            modals.push(crate::ui_save_env::show(config.gitlab.clone(), config.protection.clone(), orginal, new, deployment.source.gitlab_project.clone(), deployment.path.clone(), content.raw.clone(), image.identifier.path.clone(), image.source_path.clone(), deployment.env.clone(), deployment.name.clone()));
                                         });
                                     }
                                     
                                 }
-                                
-                                
+
+                                for field in content.fields.iter_mut() {
+                                    if let Some(new_value) = crate::ui_save_field::show_field(ui, field) {
+                                        modals.push(crate::ui_save_field::show(&config.protection, field, new_value, deployment.source.gitlab_project.clone(), deployment.path.clone(), content.raw.clone(), deployment.env.clone(), deployment.name.clone()));
+                                    }
+                                }
                                 
                                 let vault_path = deployment
                                                             .source
@@ -180,7 +256,7 @@ pub fn show_project(
                                             
                                         });
                                     }, &mut secret.secrets, |orginal_secrets,new_secrets|{
-                                        modals.push(show(config.vault.connection.clone(), orginal_secrets, new_secrets,  vault_path.clone(), secret.vault_name.clone(), deployment_name.clone()));
+                                        modals.push(show(&config.protection, orginal_secrets, new_secrets,  vault_path.clone(), secret.vault_name.clone(), deployment_name.clone(), deployment.env.clone()));
                                     });
                                 }
                             },
@@ -200,6 +276,16 @@ pub fn show_project(
     });
 }
 
+pub fn show_pipeline(ui: &mut Ui, pipeline: &crate::adapters::gitlab::Pipeline) {
+    let color = match pipeline.status.as_str() {
+        "success" => egui::Color32::from_rgb(0, 160, 0),
+        "failed" => ui.visuals().error_fg_color,
+        "running" | "pending" => egui::Color32::from_rgb(30, 120, 220),
+        _ => ui.visuals().weak_text_color(),
+    };
+    ui.hyperlink_to(egui::RichText::new(&pipeline.status).color(color).small(), &pipeline.web_url);
+}
+
 pub fn show_artifact(ui: &mut Ui, artifact: & crate::adapters::harbor::Artifact, text : &str) {
     let tags : Vec<_> = artifact.tags.iter().map(|x|x.name.as_str()).collect();
     ui.label(text);
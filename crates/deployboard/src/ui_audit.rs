@@ -0,0 +1,85 @@
+use egui::Widget;
+
+pub fn show_audit(app: &mut crate::App, ctx: &mut common::app::Context<'_>) {
+    if app.show_audit {
+        let mut reload = false;
+        let modal = egui::Modal::new(egui::Id::new("audit")).show(ctx.ui.ctx(), |ui| {
+            ui.set_width(ctx.ui.available_width() * 0.9);
+            ui.set_height(ctx.ui.ctx().available_rect().height() * 0.9);
+
+            let entries: Vec<_> = app
+                .audit_entries
+                .iter()
+                .rev()
+                .filter(|entry| app.audit_filter.is_empty() || entry.matches(&app.audit_filter))
+                .filter(|entry| !app.audit_errors_only || entry.error.is_some())
+                .collect();
+
+            egui::Sides::new().show(
+                ui,
+                |ui| {
+                    ui.heading("audit");
+                    if ui.button("⟳").clicked() {
+                        reload = true;
+                    }
+                    egui::TextEdit::singleline(&mut app.audit_filter)
+                        .hint_text("filter")
+                        .ui(ui);
+                    ui.checkbox(&mut app.audit_errors_only, "errors only");
+                },
+                |ui| {
+                    if ui.button("Export").clicked() {
+                        if let Err(err) = crate::audit::export(&app.audit_file, &entries) {
+                            app.toasts.error(format!("Error exporting audit log:\n {}", err));
+                        } else {
+                            app.toasts.success(format!("Exported audit log to {}", app.audit_file));
+                        }
+                    }
+                    egui::TextEdit::singleline(&mut app.audit_file)
+                        .hint_text("export file")
+                        .ui(ui);
+                    if ui.button("Copy").clicked() {
+                        if let Ok(text) = serde_json::to_string_pretty(&entries) {
+                            ui.ctx().copy_text(text);
+                        }
+                    }
+                },
+            );
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("audit_grid").striped(true).show(ui, |ui| {
+                    ui.strong("time");
+                    ui.strong("user");
+                    ui.strong("kind");
+                    ui.strong("target");
+                    ui.strong("old");
+                    ui.strong("new");
+                    ui.strong("result");
+                    ui.end_row();
+                    for entry in entries.iter() {
+                        ui.label(entry.timestamp.format("%d.%m.%Y %H:%M:%S").to_string());
+                        ui.label(&entry.user);
+                        ui.label(format!("{:?}", entry.kind));
+                        ui.label(&entry.target);
+                        ui.monospace(&entry.old_hash[..entry.old_hash.len().min(12)]);
+                        ui.monospace(&entry.new_hash[..entry.new_hash.len().min(12)]);
+                        match &entry.error {
+                            Some(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+                            None => ui.label("ok"),
+                        };
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+        if reload {
+            app.open_audit();
+        }
+
+        if modal.should_close() {
+            app.show_audit = false;
+        }
+    }
+}
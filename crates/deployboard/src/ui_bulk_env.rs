@@ -0,0 +1,271 @@
+use egui::Widget;
+
+#[derive(Default)]
+pub struct BulkEnv {
+    pub env: String,
+    pub name: String,
+    pub value: String,
+    pub replacement: String,
+    pub commit_message: String,
+    pub guard: crate::protection::Guard,
+    /// Recomputed only when `Inputs` change, not every frame.
+    pub preview: Option<(Inputs, Result<Vec<FileChange>, String>)>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Inputs {
+    pub env: String,
+    pub name: String,
+    pub value: String,
+    pub replacement: String,
+    /// Deployments of the env whose content is loaded, "load all" changes it.
+    pub loaded: usize,
+}
+
+impl BulkEnv {
+    pub fn inputs(
+        &self,
+        project_by_name: Option<&std::collections::BTreeMap<String, crate::models::DeployProject>>,
+    ) -> Inputs {
+        Inputs {
+            env: self.env.clone(),
+            name: self.name.clone(),
+            value: self.value.clone(),
+            replacement: self.replacement.clone(),
+            loaded: project_by_name
+                .into_iter()
+                .flat_map(|x| x.values())
+                .filter_map(|project| project.deployments_by_env.get(&self.env))
+                .filter(|deployment| deployment.content.as_ref().is_some_and(|x| x.ready().is_some()))
+                .count(),
+        }
+    }
+
+    pub fn update_preview(
+        &mut self,
+        project_by_name: Option<&std::collections::BTreeMap<String, crate::models::DeployProject>>,
+    ) {
+        let inputs = self.inputs(project_by_name);
+        if self.preview.as_ref().is_some_and(|(x, _)| *x == inputs) {
+            return;
+        }
+        let changes = match project_by_name {
+            Some(project_by_name) => find_changes(&inputs, project_by_name),
+            None => Ok(vec![]),
+        };
+        self.preview = Some((inputs, changes));
+    }
+}
+
+pub struct FileChange {
+    pub project: String,
+    pub path: String,
+    pub raw: String,
+    pub images: Vec<ImageChange>,
+}
+
+pub struct ImageChange {
+    pub image_path: String,
+    pub source_path: crate::yaml::Path,
+    pub envs: Vec<crate::models::EnvVar>,
+    pub changes: Vec<(String, String, String)>,
+}
+
+/// Without a name regex every env var would match, so one is required.
+pub fn find_changes(
+    bulk: &Inputs,
+    project_by_name: &std::collections::BTreeMap<String, crate::models::DeployProject>,
+) -> Result<Vec<FileChange>, String> {
+    if bulk.name.trim().is_empty() {
+        return Err("a name regex is required".to_string());
+    }
+    let name_regex = regex::Regex::new(&bulk.name).map_err(|e| e.to_string())?;
+    let value_regex = regex::Regex::new(&bulk.value).map_err(|e| e.to_string())?;
+
+    let mut files = vec![];
+    for project in project_by_name.values() {
+        let Some(deployment) = project.deployments_by_env.get(&bulk.env) else {
+            continue;
+        };
+        let Some(content) = deployment.content.as_ref().and_then(|x| x.ready()) else {
+            continue;
+        };
+        let mut images = vec![];
+        for image in content.images.iter() {
+            let mut envs = image.envs.clone();
+            let mut changes = vec![];
+            for env in envs.iter_mut() {
+                if name_regex.is_match(&env.name) && value_regex.is_match(&env.value) {
+                    let value = if bulk.value.is_empty() {
+                        bulk.replacement.clone()
+                    } else {
+                        value_regex
+                            .replace_all(&env.value, bulk.replacement.as_str())
+                            .to_string()
+                    };
+                    if value != env.value {
+                        changes.push((env.name.clone(), env.value.clone(), value.clone()));
+                        env.value = value;
+                    }
+                }
+            }
+            if !changes.is_empty() {
+                images.push(ImageChange {
+                    image_path: image.identifier.path.clone(),
+                    source_path: image.source_path.clone(),
+                    envs,
+                    changes,
+                });
+            }
+        }
+        if !images.is_empty() {
+            files.push(FileChange {
+                project: deployment.source.gitlab_project.clone(),
+                path: deployment.path.clone(),
+                raw: content.raw.clone(),
+                images,
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// One envs edit per image of the file.
+pub fn edits(
+    file: &FileChange,
+    config: &crate::adapters::gitlab::Config,
+) -> Vec<(crate::yaml::Path, serde_yaml::Value)> {
+    file.images
+        .iter()
+        .map(|image| {
+            let mut envs_path = crate::yaml::path_from_str(&config.envs_path);
+            crate::yaml::enrich_path_with_indices(&mut envs_path, &image.source_path);
+            let envs = serde_yaml::Value::Sequence(
+                image
+                    .envs
+                    .iter()
+                    .map(|env| crate::ui_save_env::generate_env(env.name.clone(), env.value.clone(), config))
+                    .collect(),
+            );
+            (envs_path, envs)
+        })
+        .collect()
+}
+
+pub fn show_bulk_env(app: &mut crate::App, ctx: &mut common::app::Context<'_>) {
+    if app.show_bulk_env {
+        let mut save = false;
+        let mut load = false;
+        let modal = egui::Modal::new(egui::Id::new("bulk_env")).show(ctx.ui.ctx(), |ui| {
+            ui.set_width(ctx.ui.available_width() * 0.9);
+            ui.set_height(ctx.ui.ctx().available_rect().height() * 0.9);
+
+            let bulk = &mut app.bulk_env;
+            if bulk.guard.env != bulk.env {
+                bulk.guard = crate::protection::Guard::new(&app.config.protection, &bulk.env);
+            }
+            ui.heading("Bulk edit envs");
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("env")
+                    .selected_text(&bulk.env)
+                    .show_ui(ui, |ui| {
+                        for env in app.config.envs.iter() {
+                            ui.selectable_value(&mut bulk.env, env.clone(), env);
+                        }
+                    });
+                if ui.button("load all").clicked() {
+                    load = true;
+                }
+            });
+            egui::Grid::new("bulk_env_grid").show(ui, |ui| {
+                ui.label("name regex");
+                egui::TextEdit::singleline(&mut bulk.name).hint_text("^LOG_LEVEL$").ui(ui);
+                ui.end_row();
+                ui.label("value regex");
+                egui::TextEdit::singleline(&mut bulk.value).hint_text("^debug$").ui(ui);
+                ui.end_row();
+                ui.label("replace with");
+                egui::TextEdit::singleline(&mut bulk.replacement).hint_text("info").ui(ui);
+                ui.end_row();
+                ui.label("commit message ");
+                egui::TextEdit::singleline(&mut bulk.commit_message).desired_width(ui.available_width()).ui(ui);
+                ui.end_row();
+            });
+
+            bulk.update_preview(app.project_by_name.ready());
+            let Some((_, changes)) = &bulk.preview else {
+                return;
+            };
+
+            ui.separator();
+            bulk.guard.show(ui);
+            egui::Sides::new().show(
+                ui,
+                |ui| {
+                    match changes {
+                        Ok(changes) => ui.label(format!("{} files affected", changes.len())),
+                        Err(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+                    };
+                },
+                |ui| {
+                    let enabled = changes.as_ref().is_ok_and(|x| !x.is_empty()) && !bulk.commit_message.is_empty() && bulk.guard.allowed();
+                    if ui.add_enabled(enabled, egui::Button::new("Stage")).clicked() {
+                        save = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        app.show_bulk_env = false;
+                    }
+                },
+            );
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for file in changes.iter().flatten() {
+                    ui.label(egui::RichText::new(format!("{}/{}", file.project, file.path)).strong());
+                    for image in file.images.iter() {
+                        for (name, old, new) in image.changes.iter() {
+                            ui.monospace(format!("  {} {}: {} -> {}", image.image_path, name, old, new));
+                        }
+                    }
+                }
+            });
+        });
+
+        if load {
+            if let Some(project_by_name) = app.project_by_name.ready_mut() {
+                for project in project_by_name.values_mut() {
+                    if let Some(deployment) = project.deployments_by_env.get_mut(&app.bulk_env.env) {
+                        if deployment.content.is_none() {
+                            crate::core::fill_deployment(deployment, &app.config, ctx.ui.ctx().clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        if save {
+            let changes = match app.bulk_env.preview.take() {
+                Some((_, Ok(changes))) => changes,
+                _ => vec![],
+            };
+            for file in changes.iter() {
+                app.changes.stage(crate::changes::Change {
+                    description: format!("bulk env {}", file.path),
+                    env: app.bulk_env.env.clone(),
+                    commit_message: app.bulk_env.commit_message.clone(),
+                    require_merge_request: app.bulk_env.guard.require_merge_request(),
+                    kind: crate::changes::ChangeKind::File {
+                        project: file.project.clone(),
+                        path: file.path.clone(),
+                        raw: file.raw.clone(),
+                        edits: edits(file, &app.config.gitlab),
+                    },
+                });
+            }
+            app.show_bulk_env = false;
+        }
+
+        if modal.should_close() {
+            app.show_bulk_env = false;
+        }
+    }
+}
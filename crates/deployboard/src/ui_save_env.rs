@@ -36,6 +36,7 @@ pub fn generate_envs(
 
 pub fn show(
     config: crate::adapters::gitlab::Config,
+    protection: crate::protection::ProtectionConfig,
     orginal_map: &std::collections::BTreeMap<String, String>,
     new_map: std::collections::BTreeMap<String, String>,
     project_id: String,
@@ -56,6 +57,7 @@ pub fn show(
         "{} {}: Update Env for {}",
         deployment_env, deployment_name, image_path
     );
+    let mut guard = crate::protection::Guard::new(&protection, &deployment_env);
     crate::models::Modal::new(
         format!("env for {}", image_path),
         move |ui: &mut egui::Ui, ctx: &mut ModalContext| {
@@ -74,45 +76,29 @@ pub fn show(
             });
 
             ui.separator();
+            guard.show(ui);
             egui::Sides::new().show(
                 ui,
                 |_ui| {},
                 |ui| {
-                    if ui.button("Save").clicked() {
-                        ctx.close = true;
-
-                        let mut yaml = serde_yaml::from_str::<serde_yaml::Value>(&raw).unwrap();
-
+                    if ui.add_enabled(guard.allowed(), egui::Button::new("Stage")).clicked() {
                         let mut envs_path = crate::yaml::path_from_str(&config.envs_path);
                         crate::yaml::enrich_path_with_indices(&mut envs_path, &image_source_path);
                         let envs = generate_envs(new_map.clone(), &config);
-                        crate::yaml::set_field(&mut yaml, &envs_path, &envs, false);
-                        let new_text = serde_yaml::to_string(&yaml).unwrap();
-
-                        // println!("{}", new_text);
 
                         ctx.close = true;
-                        let update_result = crate::adapters::gitlab::update_file(
-                            &config.connection,
-                            &project_id,
-                            &file_path,
-                            &crate::adapters::gitlab::FileUpdate {
-                                branch: "main".to_string(),
-                                commit_message: commit_message.clone(),
-                                content: new_text,
-                                author_email: config.author.email.clone(),
-                                author_name: config.author.name.clone(),
+                        ctx.changes.stage(crate::changes::Change {
+                            description: format!("Env for {}", image_path),
+                            env: deployment_env.clone(),
+                            commit_message: commit_message.clone(),
+                            require_merge_request: guard.require_merge_request(),
+                            kind: crate::changes::ChangeKind::File {
+                                project: project_id.clone(),
+                                path: file_path.clone(),
+                                raw: raw.clone(),
+                                edits: vec![(envs_path, envs)],
                             },
-                        );
-
-                        if let Err(err) = update_result {
-                            ctx.toasts
-                                .error(format!("Error Updating Env for {}:\n {}", image_path, err));
-                        } else {
-                            ctx.toasts
-                                .success(format!("Updating Env for {} succeed!", image_path));
-                            ctx.reload = true;
-                        }
+                        });
                     }
                     if ui.button("Cancel").clicked() {
                         ctx.close = true;
@@ -0,0 +1,116 @@
+use egui::Widget;
+
+use crate::models::ModalContext;
+
+pub fn show(
+    protection: &crate::protection::ProtectionConfig,
+    field: &crate::models::FieldValue,
+    new_value: serde_yaml::Value,
+    project_id: String,
+    file_path: String,
+    raw: String,
+    deployment_env: String,
+    deployment_name: String,
+) -> crate::models::Modal {
+    let name = field.field.name.clone();
+    let old = field.value.clone();
+    let new = field.edit.clone();
+    let source_path = field.source_path.clone();
+    let mut commit_message = format!(
+        "{} {}: set {} to {}",
+        deployment_env, deployment_name, name, new
+    );
+    let mut guard = crate::protection::Guard::new(protection, &deployment_env);
+    crate::models::Modal::new(
+        format!("field {} {} {}", deployment_env, deployment_name, name),
+        move |ui: &mut egui::Ui, ctx: &mut ModalContext| {
+            ui.set_width(750.0);
+
+            ui.heading(format!("{} {}: Update {}", deployment_env, deployment_name, name));
+
+            ui.horizontal(|ui| {
+                ui.label("commit message ");
+                egui::TextEdit::singleline(&mut commit_message)
+                    .desired_width(ui.available_width())
+                    .ui(ui);
+            });
+
+            ui.columns(2, |columns: &mut [egui::Ui]| {
+                columns[0].label("old");
+                columns[0].monospace(&old);
+                columns[1].label("new");
+                columns[1].monospace(&new);
+            });
+
+            ui.separator();
+            guard.show(ui);
+            egui::Sides::new().show(
+                ui,
+                |_ui| {},
+                |ui| {
+                    if ui.add_enabled(guard.allowed(), egui::Button::new("Stage")).clicked() {
+                        ctx.close = true;
+                        ctx.changes.stage(crate::changes::Change {
+                            description: format!("{} for {}", name, deployment_name),
+                            env: deployment_env.clone(),
+                            commit_message: commit_message.clone(),
+                            require_merge_request: guard.require_merge_request(),
+                            kind: crate::changes::ChangeKind::File {
+                                project: project_id.clone(),
+                                path: file_path.clone(),
+                                raw: raw.clone(),
+                                edits: vec![(source_path.clone(), new_value.clone())],
+                            },
+                        });
+                    }
+                    if ui.button("Cancel").clicked() {
+                        ctx.close = true;
+                    }
+                },
+            );
+        },
+    )
+}
+
+/// Editor matching the field type, returns the parsed value once `Save` is clicked.
+pub fn show_field(ui: &mut egui::Ui, field: &mut crate::models::FieldValue) -> Option<serde_yaml::Value> {
+    let mut save = None;
+    ui.horizontal(|ui| {
+        ui.label(format!("{}: ", field.field.name));
+        match &field.field.r#type {
+            crate::config::FieldType::Bool => {
+                let mut checked = field.edit == "true";
+                if ui.checkbox(&mut checked, "").changed() {
+                    field.edit = checked.to_string();
+                }
+            }
+            crate::config::FieldType::Enum(values) => {
+                egui::ComboBox::from_id_salt(ui.next_auto_id())
+                    .selected_text(&field.edit)
+                    .show_ui(ui, |ui| {
+                        for value in values.iter() {
+                            ui.selectable_value(&mut field.edit, value.clone(), value);
+                        }
+                    });
+            }
+            crate::config::FieldType::Number | crate::config::FieldType::String => {
+                egui::TextEdit::singleline(&mut field.edit)
+                    .desired_width(120.0)
+                    .ui(ui);
+            }
+        }
+        if field.edit != field.value {
+            let parsed = field.field.r#type.parse(&field.edit);
+            if let Err(err) = &parsed {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            if ui.add_enabled(parsed.is_ok(), egui::Button::new("Save")).clicked() {
+                save = parsed.ok();
+            }
+            if ui.button("Reset").clicked() {
+                field.edit = field.value.clone();
+            }
+        }
+    });
+    save
+}
@@ -3,13 +3,16 @@ use egui::Widget;
 use crate::models::ModalContext;
 
 pub fn show(
-    config: crate::adapters::vault::ConnectionConfig,
+    protection: &crate::protection::ProtectionConfig,
     orginal: &std::collections::BTreeMap<String, String>,
     new: std::collections::BTreeMap<String, String>,
     vault_path: String,
     vault_name: String,
     deployment_name: String,
+    deployment_env: String,
 ) -> crate::models::Modal {
+    let mut guard = crate::protection::Guard::for_vault(protection, &deployment_env);
+    let orginal_map = orginal.clone();
     let orginal = ReadOnlyTextBuffer {
         text: serde_json::to_string_pretty(&orginal).unwrap(),
     };
@@ -32,23 +35,24 @@ pub fn show(
             });
 
             ui.separator();
+            guard.show(ui);
             egui::Sides::new().show(
                 ui,
                 |_ui| {},
                 |ui| {
-                    if ui.button("Save").clicked() {
+                    if ui.add_enabled(guard.allowed(), egui::Button::new("Stage")).clicked() {
                         ctx.close = true;
-                        let update_result =crate::adapters::vault::update_secret(
-                            &config,
-                            &format!("{}/{}", vault_path, vault_name),
-                            &new,
-                        );
-                        if let Err(err) = update_result {
-                            ctx.toasts.error(format!("Error Updating {} / {}:\n {}",  vault_path, vault_name, err));
-                        } else {
-                            ctx.toasts.success(format!("Updating {} / {} succeed!",  vault_path, vault_name));
-                            ctx.reload = true;
-                        }
+                        ctx.changes.stage(crate::changes::Change {
+                            description: format!("secret {} for {}", vault_name, deployment_name),
+                            env: deployment_env.clone(),
+                            commit_message: format!("{} {}: update secret {}", deployment_env, deployment_name, vault_name),
+                            require_merge_request: false,
+                            kind: crate::changes::ChangeKind::Secret {
+                                path: format!("{}/{}", vault_path, vault_name),
+                                old: orginal_map.clone(),
+                                new: new.clone(),
+                            },
+                        });
                     }
                     if ui.button("Cancel").clicked() {
                         ctx.close = true;
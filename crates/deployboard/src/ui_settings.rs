@@ -1,10 +1,46 @@
+use egui::Widget;
+
 pub fn show_settings(app: &mut crate::App, ctx: &mut common::app::Context<'_>) {
     if app.show_settings {
         let mut save = false;
+        let mut import = None;
         let modal = egui::Modal::new(egui::Id::new("settings")).show(ctx.ui.ctx(), |ui| {
-           
+
             ui.set_width(ctx.ui.available_width() * 0.9);
             ui.set_height(ctx.ui.ctx().available_rect().height() * 0.9);
+            ui.horizontal(|ui| {
+                egui::TextEdit::singleline(&mut app.config_file)
+                    .hint_text("config file")
+                    .ui(ui);
+                if ui.button("Export").clicked() {
+                    if let Err(err) = app.config.export(&app.config_file) {
+                        app.toasts.error(format!("Error exporting settings:\n {}", err));
+                    } else {
+                        app.toasts.success(format!("Exported settings to {}", app.config_file));
+                    }
+                }
+                if ui.button("Import").clicked() {
+                    match app.config.import(&app.config_file) {
+                        Ok(config) => import = Some(config),
+                        Err(err) => {
+                            app.toasts.error(format!("Error importing settings:\n {}", err));
+                        }
+                    }
+                }
+                ui.separator();
+                egui::TextEdit::singleline(&mut app.new_workspace)
+                    .hint_text("workspace name")
+                    .ui(ui);
+                if ui
+                    .add_enabled(!app.new_workspace.is_empty(), egui::Button::new("Add workspace"))
+                    .clicked()
+                {
+                    let mut config = app.config.orignal_data.clone();
+                    config.add_workspace(&app.new_workspace);
+                    app.new_workspace = Default::default();
+                    import = Some(config);
+                }
+            });
             crate::ui::show_text(
                 ui, "settings_text",
                 |ui| {
@@ -25,6 +61,12 @@ pub fn show_settings(app: &mut crate::App, ctx: &mut common::app::Context<'_>) {
             app.reload(true);
         }
 
+        if let Some(config) = import {
+            app.config.set(config);
+            ctx.save(&app.config.orignal_data);
+            app.reload(true);
+        }
+
         if modal.should_close() {
             app.show_settings = false;
         }
@@ -0,0 +1,298 @@
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct WatchConfig {
+    pub enabled: bool,
+    /// At least `MIN_INTERVAL_SECS`, shorter intervals hammer gitlab and harbor.
+    pub interval_secs: u64,
+}
+
+pub const MIN_INTERVAL_SECS: u64 = 30;
+
+impl WatchConfig {
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.max(MIN_INTERVAL_SECS))
+    }
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 300,
+        }
+    }
+}
+
+/// What the watcher needs from a loaded deployment, cloned so polling can run off the ui thread.
+#[derive(Clone)]
+pub struct WatchedDeployment {
+    pub project: String,
+    pub path: String,
+    pub raw: String,
+    pub env: String,
+    pub name: String,
+    pub auto_update: bool,
+    pub images: Vec<crate::models::Image>,
+}
+
+#[derive(Clone)]
+pub enum WatchEventKind {
+    NewArtifact {
+        deployment: WatchedDeployment,
+        image: crate::models::Image,
+        artifact: crate::adapters::harbor::Artifact,
+    },
+    FileChanged,
+}
+
+#[derive(Clone)]
+pub struct WatchEvent {
+    pub key: String,
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub message: String,
+    pub kind: WatchEventKind,
+}
+
+#[derive(Default)]
+pub struct Watcher {
+    pub last_poll: Option<f64>,
+    pub poll: Option<poll_promise::Promise<Vec<WatchEvent>>>,
+    pub events: Vec<WatchEvent>,
+    pub seen: std::collections::HashSet<String>,
+    pub show: bool,
+}
+
+pub fn watched_deployments(
+    project_by_name: &std::collections::BTreeMap<String, crate::models::DeployProject>,
+) -> Vec<WatchedDeployment> {
+    let mut watched = vec![];
+    for project in project_by_name.values() {
+        for deployment in project.deployments_by_env.values() {
+            if let Some(content) = deployment.content.as_ref().and_then(|x| x.ready()) {
+                watched.push(WatchedDeployment {
+                    project: deployment.source.gitlab_project.clone(),
+                    path: deployment.path.clone(),
+                    raw: content.raw.clone(),
+                    env: deployment.env.clone(),
+                    name: deployment.name.clone(),
+                    auto_update: deployment.source.auto_update_env.as_ref() == Some(&deployment.env),
+                    images: content.images.clone(),
+                });
+            }
+        }
+    }
+    watched
+}
+
+pub async fn poll(
+    config: &crate::config::Config,
+    deployments: Vec<WatchedDeployment>,
+) -> Vec<WatchEvent> {
+    let mut events = vec![];
+    for deployment in deployments.into_iter() {
+        if let Ok(raw) = crate::adapters::gitlab::get_filecontent(
+            &config.gitlab.connection,
+            &deployment.project,
+            &deployment.path,
+            "main",
+        )
+        .await
+        {
+            if raw != deployment.raw {
+                events.push(WatchEvent {
+                    key: format!("{}/{}:{}", deployment.project, deployment.path, crate::audit::hash(&raw)),
+                    time: chrono::Utc::now(),
+                    message: format!("{} {}: {} was changed", deployment.env, deployment.name, deployment.path),
+                    kind: WatchEventKind::FileChanged,
+                });
+            }
+        }
+        for image in deployment.images.iter() {
+            let newest = crate::adapters::harbor::get_artifacts(
+                &config.harbor.connection,
+                &image.identifier.project,
+                &image.identifier.path,
+                "-push_time",
+                1,
+            )
+            .await
+            .ok()
+            .and_then(|artifacts| artifacts.into_iter().next());
+            if let Some(artifact) = newest {
+                if artifact.push_time > image.artifact.push_time && !artifact.tags.is_empty() {
+                    events.push(WatchEvent {
+                        key: format!("{}/{}:{}", deployment.env, image.identifier.path, artifact.tags[0].name),
+                        time: chrono::Utc::now(),
+                        message: format!(
+                            "{} {}: new image {}:{}",
+                            deployment.env, deployment.name, image.identifier.path, artifact.tags[0].name
+                        ),
+                        kind: WatchEventKind::NewArtifact {
+                            deployment: deployment.clone(),
+                            image: image.clone(),
+                            artifact,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    events
+}
+
+/// The file is fetched again first, if it changed since it was loaded the update is skipped
+/// instead of committing on top of the old content and overwriting that change.
+pub fn auto_update(
+    gitlab: &crate::adapters::gitlab::Config,
+    audit: &crate::audit::AuditLog,
+    deployment: &WatchedDeployment,
+    image: &crate::models::Image,
+    artifact: &crate::adapters::harbor::Artifact,
+) -> impl std::future::Future<Output = crate::models::JobResult> + use<> {
+    let gitlab = gitlab.clone();
+    let audit = audit.clone();
+    let deployment = deployment.clone();
+    let source_path = image.source_path.clone();
+    let tag = artifact.tags[0].name.clone();
+    let new_image = image.identifier.to_string_with_tag(&tag);
+    async move {
+        let current = match crate::adapters::gitlab::get_file(
+            &gitlab.connection,
+            &deployment.project,
+            &deployment.path,
+            "main",
+        )
+        .await
+        {
+            Ok(current) => current,
+            Err(err) => {
+                return crate::models::JobResult::error(format!(
+                    "Error auto updating {}:\n {}",
+                    deployment.name, err
+                ));
+            }
+        };
+        if current.content != deployment.raw {
+            return crate::models::JobResult::error(format!(
+                "Skipped auto update of {} {} to {}:\n {} was changed since it was loaded, reload to see the change",
+                deployment.env, deployment.name, tag, deployment.path
+            ));
+        }
+        let new_text = match serde_yaml::from_str::<serde_yaml::Value>(&current.content).and_then(|mut yaml| {
+            crate::yaml::set_field(&mut yaml, &source_path, &serde_yaml::Value::String(new_image.clone()), false);
+            serde_yaml::to_string(&yaml)
+        }) {
+            Ok(new_text) => new_text,
+            Err(err) => {
+                return crate::models::JobResult::error(format!(
+                    "Error auto updating {}:\n {}",
+                    deployment.name, err
+                ));
+            }
+        };
+        crate::core::commit_file_job(
+            gitlab,
+            audit,
+            crate::core::FileCommit {
+                project: deployment.project.clone(),
+                path: deployment.path.clone(),
+                raw: current.content,
+                new_text,
+                last_commit_id: Some(current.last_commit_id),
+                commit_message: format!("{} {}: auto update image to {}", deployment.env, deployment.name, tag),
+                require_merge_request: false,
+            },
+            format!("{} to {}", deployment.name, new_image),
+        )
+        .await
+    }
+}
+
+impl crate::App {
+    pub fn watch(&mut self, ctx: &egui::Context) {
+        if !self.config.watch.enabled {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let interval = self.config.watch.interval();
+        ctx.request_repaint_after(interval);
+        let interval = interval.as_secs_f64();
+
+        if let Some(events) = self.watcher.poll.as_ref().and_then(|x| x.ready()) {
+            for event in events.iter() {
+                if !self.watcher.seen.insert(event.key.clone()) {
+                    continue;
+                }
+                self.toasts.info(event.message.clone());
+                if let WatchEventKind::NewArtifact { deployment, image, artifact } = &event.kind {
+                    if deployment.auto_update && !self.config.protection.unattended(&deployment.env) {
+                        self.toasts.warning(format!("Skipped auto update of protected env {}", deployment.env));
+                    } else if deployment.auto_update {
+                        self.jobs.spawn(auto_update(&self.config.gitlab, &self.audit, deployment, image, artifact));
+                    }
+                }
+                self.watcher.events.push(event.clone());
+            }
+            self.watcher.poll = None;
+        }
+
+        let due = self.watcher.last_poll.is_none_or(|last| now - last >= interval);
+        if due && self.watcher.poll.is_none() {
+            if let Some(project_by_name) = self.project_by_name.ready() {
+                self.watcher.last_poll = Some(now);
+                let deployments = watched_deployments(project_by_name);
+                let config = self.config.orignal_data.clone();
+                let (sender, promise) = poll_promise::Promise::new();
+                let ctx = ctx.clone();
+                common::execute(async move {
+                    let events = poll(&config, deployments).await;
+                    sender.send(events);
+                    ctx.request_repaint();
+                });
+                self.watcher.poll = Some(promise);
+            }
+        }
+    }
+}
+
+pub fn show_notifications(app: &mut crate::App, ctx: &mut common::app::Context<'_>) {
+    if app.watcher.show {
+        let modal = egui::Modal::new(egui::Id::new("notifications")).show(ctx.ui.ctx(), |ui| {
+            ui.set_width(ctx.ui.available_width() * 0.6);
+            egui::Sides::new().show(
+                ui,
+                |ui| {
+                    ui.heading("notifications");
+                },
+                |ui| {
+                    if ui.button("Clear").clicked() {
+                        app.watcher.events.clear();
+                    }
+                },
+            );
+            ui.separator();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for event in app.watcher.events.iter().rev() {
+                    ui.horizontal(|ui| {
+                        ui.label(event.time.format("%d.%m.%Y %H:%M").to_string());
+                        ui.label(&event.message);
+                    });
+                }
+            });
+        });
+
+        if modal.should_close() {
+            app.watcher.show = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn interval_has_a_minimum() {
+        let mut config = super::WatchConfig::default();
+        assert_eq!(config.interval().as_secs(), 300);
+        config.interval_secs = 0;
+        assert_eq!(config.interval().as_secs(), super::MIN_INTERVAL_SECS);
+    }
+}
@@ -175,6 +175,21 @@ pub fn set_field(
     }
 }
 
+/// The value at an exact path as built by `get_fields`, unlike `get_field` which takes a query.
+pub fn get_at<'a>(yaml: &'a serde_yaml::Value, path: &Path) -> Option<&'a serde_yaml::Value> {
+    let mut value = yaml;
+    for entry in path.iter() {
+        value = match (value, entry) {
+            (serde_yaml::Value::Sequence(values), PathEntry::Index(i)) => values.get(*i)?,
+            (serde_yaml::Value::Mapping(mapping), PathEntry::Field(name)) => {
+                mapping.get(serde_yaml::Value::String(name.to_string()))?
+            }
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
 pub fn as_string(value: &serde_yaml::Value) -> Option<String> {
     match value {
         serde_yaml::Value::Bool(b) => Some(b.to_string()),
@@ -0,0 +1,633 @@
+use forecasting_engine::optimise::{self, Metric};
+use forecasting_engine::validation::{self, ValidationSettings};
+use forecasting_engine::{ForecastModel, ForecastResult, intermittent, model, models, seasonality};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// cargo run --release --bin forecasting-cli -- demand.csv --method auto --optimise
+// the input is long format with a header row: series id, date, value, one row per series and period
+
+const USAGE: &str = "usage: forecasting-cli <input.csv> [options]
+  --method <name>|auto         method for every series, auto keeps the best of all (default auto)
+  --optimise                   optimise the parameters of every method that is tried
+  --metric mae|rmse|mape|mase  what auto and --optimise minimise (default mae)
+  --test-len <n>               last periods auto holds back to compare the methods (default 12)
+  --periods <n>                periods to forecast (default 10)
+  --season-len <n>             season length instead of detecting it per series
+  --delimiter <char>|tab       delimiter of the input (default ,)
+  --forecasts <path>           forecasts csv (default forecasts.csv)
+  --metrics <path>             metrics csv (default metrics.csv)
+  --threads <n>                worker threads (default one per core)";
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub input: String,
+    /// None tries every method and keeps the one with the best score.
+    pub method: Option<&'static str>,
+    pub optimise: bool,
+    pub metric: Metric,
+    /// Auto compares the methods on forecasts of these last periods, fitted without them.
+    pub validation: ValidationSettings,
+    pub extra_periods: usize,
+    /// None detects the period of every series.
+    pub season_len: Option<usize>,
+    pub delimiter: u8,
+    pub forecasts: String,
+    pub metrics: String,
+    pub threads: usize,
+}
+
+impl Options {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            input: String::default(),
+            method: None,
+            optimise: false,
+            metric: Metric::Mae,
+            validation: ValidationSettings {
+                enabled: true,
+                ..Default::default()
+            },
+            extra_periods: 10,
+            season_len: None,
+            delimiter: b',',
+            forecasts: "forecasts.csv".to_string(),
+            metrics: "metrics.csv".to_string(),
+            threads: std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1),
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--optimise" {
+                options.optimise = true;
+                continue;
+            }
+            if !arg.starts_with("--") {
+                if !options.input.is_empty() {
+                    return Err(format!("unexpected argument {}", arg));
+                }
+                options.input = arg;
+                continue;
+            }
+            let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            match arg.as_str() {
+                "--method" => options.method = method(&value)?,
+                "--metric" => {
+                    options.metric = Metric::ALL
+                        .into_iter()
+                        .find(|x| x.name().eq_ignore_ascii_case(&value))
+                        .ok_or_else(|| format!("unknown metric {}", value))?
+                }
+                "--periods" => options.extra_periods = number(&arg, &value)?,
+                "--test-len" => options.validation.test_len = number(&arg, &value)?.max(1),
+                "--season-len" => options.season_len = Some(number(&arg, &value)?.max(1)),
+                "--delimiter" => {
+                    options.delimiter = match value.as_bytes() {
+                        b"tab" => b'\t',
+                        [delimiter] => *delimiter,
+                        _ => return Err(format!("{:?} is not a single character delimiter", value)),
+                    }
+                }
+                "--forecasts" => options.forecasts = value,
+                "--metrics" => options.metrics = value,
+                "--threads" => options.threads = number(&arg, &value)?.max(1),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        if options.input.is_empty() {
+            return Err("no input file".to_string());
+        }
+        Ok(options)
+    }
+}
+
+fn number(arg: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} needs a number, not {:?}", arg, value))
+}
+
+/// Names as in the app, `double-exp-smooth` works as well as `"double exp smooth"`.
+fn method(value: &str) -> Result<Option<&'static str>, String> {
+    if value == "auto" {
+        return Ok(None);
+    }
+    let value = value.replace(['-', '_'], " ");
+    let names: Vec<&'static str> = models::defaults().into_iter().map(|(name, _)| name).collect();
+    names
+        .iter()
+        .find(|&&name| name.eq_ignore_ascii_case(&value))
+        .map(|&name| Some(name))
+        .ok_or_else(|| format!("unknown method {}, one of auto, {}", value, names.join(", ")))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Series {
+    pub id: String,
+    pub dates: Vec<String>,
+    pub demand: Vec<f64>,
+}
+
+/// Series in the order their ids first appear, each ordered by its date text. Only ISO dates like 2024-03-01,
+/// 2024-03 or 2024 are accepted, for them the text order is chronological, and each at most once per series.
+/// Empty values become NaN, the methods reject such series.
+pub fn read_series(text: &str, delimiter: u8) -> Result<Vec<Series>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(text.trim_start_matches('\u{feff}').as_bytes());
+    let mut series: Vec<Series> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut lines: HashMap<(String, String), u64> = HashMap::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| e.to_string())?;
+        let line = record.position().map_or(i as u64 + 2, |x| x.line());
+        let (Some(id), Some(date), Some(value)) = (record.get(0), record.get(1), record.get(2)) else {
+            return Err(format!("row {}: expected series id, date and value", i + 1));
+        };
+        let date = date.trim();
+        if !is_iso_date(date) {
+            return Err(format!(
+                "row {}: {:?} is not an ISO date like 2024-03-01, 2024-03 or 2024",
+                i + 1,
+                date
+            ));
+        }
+        let value = value.trim();
+        let value = if value.is_empty() {
+            f64::NAN
+        } else {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("row {}: {:?} is not a number", i + 1, value))?
+        };
+        if let Some(first) = lines.insert((id.trim().to_string(), date.to_string()), line) {
+            return Err(format!(
+                "line {}: series {} already has a value for {} on line {}",
+                line,
+                id.trim(),
+                date,
+                first
+            ));
+        }
+        let at = *index.entry(id.trim().to_string()).or_insert_with(|| {
+            series.push(Series {
+                id: id.trim().to_string(),
+                ..Default::default()
+            });
+            series.len() - 1
+        });
+        series[at].dates.push(date.to_string());
+        series[at].demand.push(value);
+    }
+
+    for s in series.iter_mut() {
+        let mut rows: Vec<(String, f64)> = s.dates.drain(..).zip(s.demand.drain(..)).collect();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        (s.dates, s.demand) = rows.into_iter().unzip();
+    }
+    Ok(series)
+}
+
+/// The first gap in the dates of a series, the methods take the periods as evenly spaced. Dates on the same day
+/// of every month are spaced in months, on the same day of every year in years, anything else in days.
+pub fn spacing_warning(series: &Series) -> Option<String> {
+    let dates: Vec<(i64, i64, i64)> = series
+        .dates
+        .iter()
+        .map(|date| {
+            let day = date.split(['T', ' ']).next().unwrap_or(date);
+            let mut parts = day.split('-').map(|x| x.parse::<i64>().unwrap_or(1));
+            let year = parts.next().unwrap_or(1);
+            (year, parts.next().unwrap_or(1), parts.next().unwrap_or(1))
+        })
+        .collect();
+    let index: Vec<i64> = if dates.iter().all(|x| (x.1, x.2) == (dates[0].1, dates[0].2)) {
+        dates.iter().map(|x| x.0).collect()
+    } else if dates.iter().all(|x| x.2 == dates[0].2) {
+        dates.iter().map(|x| x.0 * 12 + x.1).collect()
+    } else {
+        dates.iter().map(|&(y, m, d)| days(y, m, d)).collect()
+    };
+    let step = index.windows(2).map(|x| x[1] - x[0]).filter(|&x| x > 0).min()?;
+    let gap = index.windows(2).position(|x| x[1] - x[0] > step)?;
+    Some(format!(
+        "series {}: gap between {} and {}, the dates are not evenly spaced",
+        series.id,
+        series.dates[gap],
+        series.dates[gap + 1]
+    ))
+}
+
+/// Days since 0000-03-01 in the proleptic Gregorian calendar.
+fn days(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    era * 146097 + year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year
+}
+
+/// `date`, `date-month`, `date-month-day`, optionally followed by a time after `T` or a space.
+fn is_iso_date(text: &str) -> bool {
+    let day = text.split(['T', ' ']).next().unwrap_or(text);
+    let parts: Vec<&str> = day.split('-').collect();
+    let digits = |part: &str, len: usize| part.len() == len && part.bytes().all(|x| x.is_ascii_digit());
+    !parts.is_empty()
+        && parts.len() <= 3
+        && digits(parts[0], 4)
+        && parts[1..].iter().all(|part| digits(part, 2))
+}
+
+pub struct Outcome {
+    pub method: &'static str,
+    pub model: Box<dyn ForecastModel>,
+    pub result: ForecastResult,
+    /// Out-of-sample score auto selected the method by, None when the series was too short and the in-sample
+    /// score was used or the method was given.
+    pub validation: Option<f64>,
+}
+
+/// The defaults with the season length applied, only the selected one if a method is given.
+fn candidates(series: &Series, options: &Options) -> Vec<(&'static str, Box<dyn ForecastModel>)> {
+    let season_len = options
+        .season_len
+        .or_else(|| seasonality::detect(&series.demand).map(|x| x.len));
+    models::defaults()
+        .into_iter()
+        .filter(|(name, _)| options.method.is_none_or(|x| x == *name))
+        .map(|(name, mut model)| {
+            if let Some(season_len) = season_len {
+                model::set_season_len(model.as_mut(), season_len);
+            }
+            (name, model)
+        })
+        .collect()
+}
+
+/// A method, its model and the out-of-sample score it was selected by.
+type Selected = (&'static str, Box<dyn ForecastModel>, Option<f64>);
+
+/// Auto: every method is optimised on the series without the test window and scored on forecasts of it.
+/// Series too short for that fall back to the in-sample score.
+fn select(series: &Series, options: &Options) -> Result<Selected, String> {
+    let demand = &series.demand;
+    let fit_len = demand.len().saturating_sub(options.validation.test_len);
+    let mut validated: Option<(&'static str, Box<dyn ForecastModel>, f64)> = None;
+    let mut in_sample: Option<(&'static str, Box<dyn ForecastModel>, f64)> = None;
+    let mut errors = vec![];
+    for (name, mut model) in candidates(series, options) {
+        if options.optimise && fit_len >= 2 {
+            model = optimise::optimise(model.as_ref(), &demand[..fit_len], options.extra_periods, options.metric).model;
+        }
+        if let Ok(validation) = validation::validate(model.as_ref(), demand, &options.validation) {
+            let score = options.metric.score_errors(&validation.overall);
+            if validated.as_ref().is_none_or(|x| score < x.2) {
+                validated = Some((name, model.clone(), score));
+            }
+        }
+        match model.forecast(demand, options.extra_periods) {
+            Ok(forecast) => {
+                let score = options.metric.score(&ForecastResult::new(forecast, demand));
+                if in_sample.as_ref().is_none_or(|x| score < x.2) {
+                    in_sample = Some((name, model, score));
+                }
+            }
+            Err(err) => errors.push(format!("{}: {}", name, err)),
+        }
+    }
+    match (validated, in_sample) {
+        (Some((name, model, score)), _) => Ok((name, model, Some(score))),
+        (None, Some((name, model, _))) => Ok((name, model, None)),
+        (None, None) => Err(errors.join("; ")),
+    }
+}
+
+/// Fits the selected method, or selects one by `select` when none is given.
+pub fn forecast(series: &Series, options: &Options) -> Result<Outcome, String> {
+    let (method, model, validation) = match options.method {
+        Some(_) => {
+            let Some((name, model)) = candidates(series, options).into_iter().next() else {
+                return Err("unknown method".to_string());
+            };
+            (name, model, None)
+        }
+        None => select(series, options)?,
+    };
+    // the final parameters may use the whole series
+    let model = if options.optimise {
+        optimise::optimise(model.as_ref(), &series.demand, options.extra_periods, options.metric).model
+    } else {
+        model
+    };
+    let forecast = model
+        .forecast(&series.demand, options.extra_periods)
+        .map_err(|err| format!("{}: {}", method, err))?;
+    Ok(Outcome {
+        method,
+        model,
+        result: ForecastResult::new(forecast, &series.demand),
+        validation,
+    })
+}
+
+/// A panic in one method fails its series only, not the whole batch.
+fn catch(f: impl FnOnce() -> Result<Outcome, String>) -> Result<Outcome, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format!("forecast panicked: {}", message))
+    })
+}
+
+/// Every series on `options.threads` workers, each takes the next series until none are left.
+/// The outcomes are in the order of the series.
+pub fn forecast_all(series: &[Series], options: &Options) -> Vec<Result<Outcome, String>> {
+    let next = AtomicUsize::new(0);
+    let mut outcomes: Vec<(usize, Result<Outcome, String>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.threads.min(series.len()).max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(s) = series.get(i) else {
+                            break;
+                        };
+                        done.push((i, catch(|| forecast(s, options))));
+                    }
+                    done
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            // a worker only panics outside of `catch`, its series are then missing and reported below
+            .flat_map(|x| x.join().unwrap_or_default())
+            .collect()
+    });
+    outcomes.sort_by_key(|x| x.0);
+    let mut outcomes = outcomes.into_iter().peekable();
+    (0..series.len())
+        .map(|i| match outcomes.next_if(|x| x.0 == i) {
+            Some((_, outcome)) => outcome,
+            None => Err("forecast worker panicked".to_string()),
+        })
+        .collect()
+}
+
+/// NaN, e.g. before a moving average has a full window, becomes an empty cell.
+fn cell(value: f64) -> String {
+    if value.is_finite() { value.to_string() } else { String::default() }
+}
+
+/// Long format like the input, periods after the demand have no date.
+pub fn forecasts_csv(series: &[Series], outcomes: &[Result<Outcome, String>]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer
+        .write_record(["series", "period", "date", "demand", "forecast", "method"])
+        .map_err(|e| e.to_string())?;
+    for (s, outcome) in series.iter().zip(outcomes) {
+        let Ok(outcome) = outcome else {
+            continue;
+        };
+        for (i, &forecast) in outcome.result.forecast.iter().enumerate() {
+            writer
+                .write_record([
+                    s.id.clone(),
+                    i.to_string(),
+                    s.dates.get(i).cloned().unwrap_or_default(),
+                    s.demand.get(i).map(|&x| cell(x)).unwrap_or_default(),
+                    cell(forecast),
+                    outcome.method.to_string(),
+                ])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// One row per series, series that could not be forecast only have the error.
+pub fn metrics_csv(series: &[Series], outcomes: &[Result<Outcome, String>]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer
+        .write_record([
+            "series",
+            "periods",
+            "class",
+            "method",
+            "parameters",
+            "bias",
+            "mape",
+            "mae",
+            "rmse",
+            "mase",
+            "validation",
+            "error",
+        ])
+        .map_err(|e| e.to_string())?;
+    for (s, outcome) in series.iter().zip(outcomes) {
+        let class = intermittent::classify(&s.demand)
+            .map(|x| x.class.name().to_string())
+            .unwrap_or_default();
+        let row = match outcome {
+            Ok(outcome) => {
+                let r = &outcome.result;
+                [
+                    outcome.method.to_string(),
+                    outcome.model.describe(),
+                    cell(r.bias_abs),
+                    cell(r.mape),
+                    cell(r.mae),
+                    cell(r.rmse),
+                    cell(r.mase),
+                    outcome.validation.map(cell).unwrap_or_default(),
+                    String::default(),
+                ]
+            }
+            Err(err) => {
+                let mut row: [String; 9] = Default::default();
+                row[8] = err.clone();
+                row
+            }
+        };
+        let mut record = vec![s.id.clone(), s.demand.len().to_string(), class];
+        record.extend(row);
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let text = std::fs::read_to_string(&options.input).map_err(|e| format!("{}: {}", options.input, e))?;
+    let series = read_series(&text, options.delimiter).map_err(|e| format!("{}: {}", options.input, e))?;
+    for warning in series.iter().filter_map(spacing_warning) {
+        eprintln!("warning: {}", warning);
+    }
+    let start = std::time::Instant::now();
+    let outcomes = forecast_all(&series, options);
+    let elapsed = start.elapsed();
+
+    std::fs::write(&options.forecasts, forecasts_csv(&series, &outcomes)?)
+        .map_err(|e| format!("{}: {}", options.forecasts, e))?;
+    std::fs::write(&options.metrics, metrics_csv(&series, &outcomes)?)
+        .map_err(|e| format!("{}: {}", options.metrics, e))?;
+
+    let failed = outcomes.iter().filter(|x| x.is_err()).count();
+    eprintln!(
+        "{} series forecast in {:.1} s, {} failed",
+        series.len() - failed,
+        elapsed.as_secs_f64(),
+        failed
+    );
+    if failed > 0 {
+        eprintln!("the error column of {} says why", options.metrics);
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|x| x == "--help" || x == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("err :{}\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(err) = run(&options) {
+        eprintln!("err :{}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> Options {
+        Options::parse(args.iter().map(|x| x.to_string())).unwrap()
+    }
+
+    #[test]
+    fn parses_options() {
+        let o = options(&["in.csv", "--method", "double-exp-smooth", "--metric", "RMSE", "--optimise", "--threads", "0"]);
+        assert_eq!(o.input, "in.csv");
+        assert_eq!(o.method, Some("double exp smooth"));
+        assert_eq!(o.metric, Metric::Rmse);
+        assert!(o.optimise);
+        assert_eq!(o.threads, 1);
+        assert_eq!(options(&["in.csv", "--method", "auto"]).method, None);
+        assert!(Options::parse(["in.csv".to_string(), "--method".to_string(), "magic".to_string()]).is_err());
+        assert!(Options::parse(["--optimise".to_string()]).is_err());
+    }
+
+    #[test]
+    fn groups_and_sorts_series() {
+        let text = "id,date,value\na,2024-02,2\nb,2024-01,5\na,2024-01,1\nb,2024-02,\n";
+        let series = read_series(text, b',').unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].id, "a");
+        assert_eq!(series[0].dates, ["2024-01", "2024-02"]);
+        assert_eq!(series[0].demand, [1., 2.]);
+        assert!(series[1].demand[1].is_nan());
+        assert!(read_series("id,date,value\na,2024-01,x\n", b',').is_err());
+        assert!(read_series("id,date,value\na,2024-01-05T10:00,1\nb,2024,2\n", b',').is_ok());
+        // would sort as text in the wrong order
+        for date in ["01.02.2024", "2024-1", "1/2/2024", "24-01"] {
+            assert!(read_series(&format!("id,date,value\na,{},1\n", date), b',').is_err(), "{}", date);
+        }
+    }
+
+    #[test]
+    fn duplicate_dates_are_an_error() {
+        let err = read_series("id,date,value\na,2024-01,1\nb,2024-01,2\na,2024-01,3\n", b',').unwrap_err();
+        assert_eq!(err, "line 4: series a already has a value for 2024-01 on line 2");
+    }
+
+    #[test]
+    fn gaps_are_reported() {
+        let series = |dates: &[&str]| Series {
+            id: "a".to_string(),
+            dates: dates.iter().map(|x| x.to_string()).collect(),
+            demand: vec![1.; dates.len()],
+        };
+        for dates in [
+            &["2024-01", "2024-02", "2024-03"][..],
+            &["2024-01-01", "2024-02-01", "2024-03-01"],
+            &["2024-02-26", "2024-03-04", "2024-03-11"],
+            &["2023", "2024"],
+            &[],
+        ] {
+            assert_eq!(spacing_warning(&series(dates)), None, "{:?}", dates);
+        }
+        assert_eq!(
+            spacing_warning(&series(&["2024-01", "2024-02", "2024-04"])).unwrap(),
+            "series a: gap between 2024-02 and 2024-04, the dates are not evenly spaced"
+        );
+        assert!(spacing_warning(&series(&["2024-02-28", "2024-02-29", "2024-03-02"])).is_some());
+    }
+
+    #[test]
+    fn auto_selects_out_of_sample() {
+        let seasonal: Vec<f64> = (0..36).map(|i| 100. + 20. * ((i % 12) as f64 / 12. * std::f64::consts::TAU).sin()).collect();
+        let series = Series {
+            id: "seasonal".to_string(),
+            dates: vec![],
+            demand: seasonal.clone(),
+        };
+        let outcome = forecast(&series, &options(&["in.csv"])).unwrap();
+        assert!(outcome.validation.is_some_and(|x| x.is_finite()), "{}", outcome.method);
+        assert_eq!(outcome.result.forecast.len(), 46);
+
+        // too short for a test window of 12, selected in-sample
+        let short = Series {
+            demand: seasonal[..8].to_vec(),
+            ..series.clone()
+        };
+        let outcome = forecast(&short, &options(&["in.csv"])).unwrap();
+        assert!(outcome.validation.is_none());
+        let outcome = forecast(&short, &options(&["in.csv", "--test-len", "3"])).unwrap();
+        assert!(outcome.validation.is_some());
+    }
+
+    #[test]
+    fn panics_fail_only_their_series() {
+        let outcome = catch(|| panic!("bad series"));
+        assert_eq!(outcome.err().unwrap(), "forecast panicked: bad series");
+    }
+
+    #[test]
+    fn forecasts_every_series_in_order() {
+        let mut text = "id,date,value\n".to_string();
+        for id in 0..20 {
+            for month in 1..=12 {
+                text += &format!("s{},2024-{:02},{}\n", id, month, id * 10 + month);
+            }
+        }
+        text += "missing,2024-01,\nmissing,2024-02,3\n";
+        let series = read_series(&text, b',').unwrap();
+        let o = options(&["in.csv", "--periods", "3", "--threads", "4"]);
+        let outcomes = forecast_all(&series, &o);
+        assert_eq!(outcomes.len(), 21);
+        for (s, outcome) in series.iter().zip(&outcomes).take(20) {
+            let outcome = outcome.as_ref().unwrap();
+            assert_eq!(outcome.result.forecast.len(), 15, "{}", s.id);
+            assert!(outcome.result.forecast[12..].iter().all(|x| x.is_finite()));
+        }
+        assert!(outcomes[20].is_err());
+
+        let metrics = metrics_csv(&series, &outcomes).unwrap();
+        assert_eq!(metrics.lines().count(), 22);
+        let forecasts = forecasts_csv(&series, &outcomes).unwrap();
+        assert_eq!(forecasts.lines().count(), 1 + 20 * 15);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::optimise::nelder_mead;
+
+/// (p, d, q)(P, D, Q) with seasonal period `season_len`, the seasonal part is ignored for a period of 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Order {
+    pub p: usize,
+    pub d: usize,
+    pub q: usize,
+    pub seasonal_p: usize,
+    pub seasonal_d: usize,
+    pub seasonal_q: usize,
+    pub season_len: usize,
+}
+
+impl Order {
+    fn seasonal(&self) -> bool {
+        self.season_len > 1
+    }
+
+    fn coefficients(&self) -> usize {
+        let seasonal = if self.seasonal() {
+            self.seasonal_p + self.seasonal_q
+        } else {
+            0
+        };
+        self.p + self.q + seasonal
+    }
+
+    fn differenced(&self) -> bool {
+        self.d > 0 || (self.seasonal() && self.seasonal_d > 0)
+    }
+
+    pub fn describe(&self) -> String {
+        if self.seasonal() {
+            format!(
+                "({}, {}, {})({}, {}, {}){}",
+                self.p, self.d, self.q, self.seasonal_p, self.seasonal_d, self.seasonal_q, self.season_len
+            )
+        } else {
+            format!("({}, {}, {})", self.p, self.d, self.q)
+        }
+    }
+}
+
+/// Fitted model, `ar` includes the seasonal and differencing polynomials so it applies to the undifferenced series.
+#[derive(Debug, Clone)]
+pub struct Model {
+    pub order: Order,
+    /// Lag polynomial `1 - a1 B - a2 B^2 ..`, index 0 is always 1.
+    pub ar: Vec<f64>,
+    /// Lag polynomial `1 + m1 B + m2 B^2 ..`, index 0 is always 1.
+    pub ma: Vec<f64>,
+    pub constant: f64,
+    pub sse: f64,
+    pub observations: usize,
+}
+
+impl Model {
+    pub fn aic(&self) -> f64 {
+        let parameters = self.order.coefficients() + if self.order.differenced() { 1 } else { 2 };
+        let n = self.observations as f64;
+        n * (self.sse / n).ln() + 2. * parameters as f64
+    }
+
+    /// In-sample one step ahead forecasts followed by `extra_periods` recursive ones.
+    pub fn forecast(&self, demand: &[f64], extra_periods: usize) -> Vec<f64> {
+        filter(demand, extra_periods, &self.ar, &self.ma, self.constant).0
+    }
+}
+
+fn multiply(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+/// `1 + sign * (c1 B^lag + c2 B^2lag ..)`
+fn lag_polynomial(coefficients: &[f64], lag: usize, sign: f64) -> Vec<f64> {
+    let mut polynomial = vec![0.; coefficients.len() * lag + 1];
+    polynomial[0] = 1.;
+    for (i, c) in coefficients.iter().enumerate() {
+        polynomial[(i + 1) * lag] = sign * c;
+    }
+    polynomial
+}
+
+/// Coefficients are laid out as p AR, q MA, P seasonal AR, Q seasonal MA.
+fn polynomials(order: &Order, coefficients: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let (ar, rest) = coefficients.split_at(order.p);
+    let (ma, rest) = rest.split_at(order.q);
+    let mut ar = lag_polynomial(ar, 1, -1.);
+    let mut ma = lag_polynomial(ma, 1, 1.);
+    for _ in 0..order.d {
+        ar = multiply(&ar, &[1., -1.]);
+    }
+    if order.seasonal() {
+        let (seasonal_ar, seasonal_ma) = rest.split_at(order.seasonal_p);
+        ar = multiply(&ar, &lag_polynomial(seasonal_ar, order.season_len, -1.));
+        ma = multiply(&ma, &lag_polynomial(seasonal_ma, order.season_len, 1.));
+        for _ in 0..order.seasonal_d {
+            ar = multiply(&ar, &lag_polynomial(&[1.], order.season_len, -1.));
+        }
+    }
+    (ar, ma)
+}
+
+/// Errors before the first period with a full AR history are taken as 0, future errors too.
+fn filter(demand: &[f64], extra_periods: usize, ar: &[f64], ma: &[f64], constant: f64) -> (Vec<f64>, f64, usize) {
+    let start = ar.len() - 1;
+    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
+    let mut errors = vec![0.; demand.len() + extra_periods];
+    let mut sse = 0.;
+    let mut count = 0;
+    for t in start..demand.len() + extra_periods {
+        let value = |i: usize| if i < demand.len() { demand[i] } else { forecast[i] };
+        let mut f = constant;
+        for (i, a) in ar.iter().enumerate().skip(1) {
+            f -= a * value(t - i);
+        }
+        for (j, m) in ma.iter().enumerate().skip(1).filter(|(j, _)| *j <= t) {
+            f += m * errors[t - j];
+        }
+        forecast[t] = f;
+        if t < demand.len() {
+            errors[t] = demand[t] - f;
+            sse += errors[t] * errors[t];
+            count += 1;
+        }
+    }
+    (forecast, sse, count)
+}
+
+/// Conditional least squares, the mean of an undifferenced series is estimated as the sample mean.
+pub fn fit(demand: &[f64], order: Order) -> Result<Model, String> {
+    let k = order.coefficients();
+    let (ar, _) = polynomials(&order, &vec![0.; k]);
+    let available = demand.len().saturating_sub(ar.len() - 1);
+    if available < k + 3 {
+        return Err(format!(
+            "ARIMA{} needs more than {} periods",
+            order.describe(),
+            ar.len() + k + 1
+        ));
+    }
+    let mean = if order.differenced() { 0. } else { crate::mean(demand) };
+
+    let evaluate = |coefficients: &[f64]| {
+        let (ar, ma) = polynomials(&order, coefficients);
+        let constant = mean * ar.iter().sum::<f64>();
+        let (_, sse, _) = filter(demand, 0, &ar, &ma, constant);
+        if sse.is_finite() { sse } else { f64::INFINITY }
+    };
+    let coefficients = if k == 0 {
+        vec![]
+    } else {
+        nelder_mead(evaluate, vec![0.; k], (-0.99, 0.99), 100 * k).0
+    };
+
+    let (ar, ma) = polynomials(&order, &coefficients);
+    let constant = mean * ar.iter().sum::<f64>();
+    let (_, sse, observations) = filter(demand, 0, &ar, &ma, constant);
+    Ok(Model {
+        order,
+        ar,
+        ma,
+        constant,
+        sse,
+        observations,
+    })
+}
+
+fn std_dev(data: &[f64]) -> f64 {
+    let mean = crate::mean(data);
+    (data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64).sqrt()
+}
+
+fn difference(data: &[f64], lag: usize) -> Vec<f64> {
+    (lag..data.len()).map(|i| data[i] - data[i - lag]).collect()
+}
+
+/// The differencing with the smallest standard deviation. Differencing a stationary AR(1) series
+/// scales its variance by `2(1 - phi)`, so a difference has to at least halve it to count.
+pub fn differences(demand: &[f64], season_len: usize) -> (usize, usize) {
+    let seasonal = if season_len > 1 && demand.len() >= 3 * season_len { 1 } else { 0 };
+    let mut best = ((0, 0), std_dev(demand));
+    for seasonal_d in 0..=seasonal {
+        let mut series = demand.to_vec();
+        if seasonal_d > 0 {
+            series = difference(&series, season_len);
+        }
+        for d in 0..=2 {
+            if d > 0 {
+                series = difference(&series, 1);
+            }
+            if series.len() < 3 {
+                break;
+            }
+            let deviation = std_dev(&series);
+            if deviation < best.1 * 0.7 {
+                best = ((d, seasonal_d), deviation);
+            }
+        }
+    }
+    best.0
+}
+
+/// Differencing by `differences`, then the AR and MA orders with the lowest AIC.
+pub fn auto(demand: &[f64], season_len: usize) -> Result<Model, String> {
+    let (d, seasonal_d) = differences(demand, season_len);
+    let seasonal_orders = if season_len > 1 { 1 } else { 0 };
+    let mut best: Option<Model> = None;
+    for p in 0..=2 {
+        for q in 0..=2 {
+            for seasonal_p in 0..=seasonal_orders {
+                for seasonal_q in 0..=seasonal_orders {
+                    let order = Order {
+                        p,
+                        d,
+                        q,
+                        seasonal_p,
+                        seasonal_d,
+                        seasonal_q,
+                        season_len,
+                    };
+                    let Ok(model) = fit(demand, order) else {
+                        continue;
+                    };
+                    if model.aic().is_finite() && best.as_ref().is_none_or(|x| model.aic() < x.aic()) {
+                        best = Some(model);
+                    }
+                }
+            }
+        }
+    }
+    best.ok_or_else(|| format!("no ARIMA model fits a series of {} periods", demand.len()))
+}
@@ -0,0 +1,122 @@
+/// Syntetos–Boylan demand classes, split at ADI 1.32 and CV² 0.49.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Smooth,
+    Erratic,
+    Intermittent,
+    Lumpy,
+}
+
+impl Class {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Class::Smooth => "smooth",
+            Class::Erratic => "erratic",
+            Class::Intermittent => "intermittent",
+            Class::Lumpy => "lumpy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Classification {
+    pub class: Class,
+    /// Average number of periods between two demands.
+    pub adi: f64,
+    /// Squared coefficient of variation of the non zero demand sizes.
+    pub cv2: f64,
+}
+
+impl Classification {
+    pub fn format(&self) -> String {
+        format!("{} (ADI {:.2}, CV² {:.2})", self.class.name(), self.adi, self.cv2)
+    }
+}
+
+/// `None` for a series without any demand.
+pub fn classify(demand: &[f64]) -> Option<Classification> {
+    let sizes: Vec<f64> = demand.iter().copied().filter(|&x| x != 0.).collect();
+    if sizes.is_empty() {
+        return None;
+    }
+    let adi = demand.len() as f64 / sizes.len() as f64;
+    let mean = crate::mean(&sizes);
+    let variance = sizes.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / sizes.len() as f64;
+    let cv2 = variance / (mean * mean);
+    let class = match (adi > 1.32, cv2 > 0.49) {
+        (false, false) => Class::Smooth,
+        (false, true) => Class::Erratic,
+        (true, false) => Class::Intermittent,
+        (true, true) => Class::Lumpy,
+    };
+    Some(Classification { class, adi, cv2 })
+}
+
+/// Croston's method smooths demand sizes and the intervals between them separately, the
+/// Syntetos–Boylan approximation removes its bias with a factor of `1 - alpha / 2`.
+pub fn croston(demand: &[f64], extra_periods: usize, alpha: f64, sba: bool) -> Vec<f64> {
+    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
+    let Some(first) = demand.iter().position(|&x| x != 0.) else {
+        forecast.iter_mut().for_each(|x| *x = 0.);
+        return forecast;
+    };
+    let factor = if sba { 1. - alpha / 2. } else { 1. };
+
+    let mut size = demand[first];
+    let mut interval = (first + 1) as f64;
+    let mut since = 1.;
+    for i in first + 1..demand.len() {
+        forecast[i] = factor * size / interval;
+        if demand[i] != 0. {
+            size = crate::lin_smooth(alpha, demand[i], size);
+            interval = crate::lin_smooth(alpha, since, interval);
+            since = 1.;
+        } else {
+            since += 1.;
+        }
+    }
+
+    for x in forecast[demand.len()..].iter_mut() {
+        *x = factor * size / interval;
+    }
+
+    forecast
+}
+
+/// Teunter–Syntetos–Babai: the demand probability is updated every period, so the forecast
+/// decays during long runs without demand.
+pub fn tsb(demand: &[f64], extra_periods: usize, alpha: f64, beta: f64) -> Vec<f64> {
+    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
+    let Some(first) = demand.iter().position(|&x| x != 0.) else {
+        forecast.iter_mut().for_each(|x| *x = 0.);
+        return forecast;
+    };
+
+    let mut size = demand[first];
+    let mut probability = 1. / (first + 1) as f64;
+    for i in first + 1..demand.len() {
+        forecast[i] = probability * size;
+        if demand[i] != 0. {
+            probability = crate::lin_smooth(beta, 1., probability);
+            size = crate::lin_smooth(alpha, demand[i], size);
+        } else {
+            probability = crate::lin_smooth(beta, 0., probability);
+        }
+    }
+
+    for x in forecast[demand.len()..].iter_mut() {
+        *x = probability * size;
+    }
+
+    forecast
+}
+
+/// Mean absolute one step change of the naive forecast, the denominator of MASE.
+pub fn naive_scale(demand: &[f64]) -> f64 {
+    let changes: Vec<f64> = demand
+        .windows(2)
+        .map(|x| (x[1] - x[0]).abs())
+        .filter(|x| x.is_finite())
+        .collect();
+    crate::mean(&changes)
+}
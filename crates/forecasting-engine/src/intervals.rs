@@ -0,0 +1,232 @@
+use crate::model::ForecastModel;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalMethod {
+    /// Normal quantiles of the in-sample h step ahead error variance.
+    Normal,
+    /// Quantiles of summed, resampled one step ahead residuals.
+    Bootstrap,
+}
+
+impl IntervalMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntervalMethod::Normal => "residual variance",
+            IntervalMethod::Bootstrap => "bootstrap",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntervalSettings {
+    pub enabled: bool,
+    pub method: IntervalMethod,
+    pub levels: Vec<f64>,
+    pub samples: usize,
+}
+
+impl Default for IntervalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: IntervalMethod::Normal,
+            levels: vec![0.8, 0.95],
+            samples: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Band {
+    pub level: f64,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+/// Bands for the forecast periods after the demand, `start` is the index of the first one.
+#[derive(Debug, Clone)]
+pub struct Intervals {
+    pub start: usize,
+    pub bands: Vec<Band>,
+}
+
+pub fn intervals(
+    model: &dyn ForecastModel,
+    demand: &[f64],
+    forecast: &[f64],
+    extra_periods: usize,
+    settings: &IntervalSettings,
+) -> Intervals {
+    let start = demand.len();
+    let horizon = forecast.len().saturating_sub(start).min(extra_periods);
+    let point = &forecast[start..start + horizon];
+    let bands = match settings.method {
+        IntervalMethod::Normal => {
+            let errors = step_errors(model, demand, horizon);
+            let one_step = rms(&errors.first().cloned().unwrap_or_default());
+            let sigma: Vec<f64> = (0..horizon)
+                .map(|h| {
+                    if errors[h].len() >= 2 {
+                        rms(&errors[h])
+                    } else {
+                        one_step * ((h + 1) as f64).sqrt()
+                    }
+                })
+                .collect();
+            settings
+                .levels
+                .iter()
+                .map(|&level| {
+                    let z = inverse_normal(0.5 + level / 2.);
+                    Band {
+                        level,
+                        lower: point.iter().zip(sigma.iter()).map(|(x, s)| x - z * s).collect(),
+                        upper: point.iter().zip(sigma.iter()).map(|(x, s)| x + z * s).collect(),
+                    }
+                })
+                .collect()
+        }
+        IntervalMethod::Bootstrap => {
+            let residuals: Vec<f64> = step_errors(model, demand, 1)
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            let mut random = Random::new(0x2545_f491_4f6c_dd1d);
+            let paths: Vec<Vec<f64>> = (0..horizon)
+                .map(|h| {
+                    let mut sums: Vec<f64> = (0..settings.samples)
+                        .map(|_| {
+                            (0..=h)
+                                .map(|_| {
+                                    if residuals.is_empty() {
+                                        0.
+                                    } else {
+                                        residuals[random.next() as usize % residuals.len()]
+                                    }
+                                })
+                                .sum()
+                        })
+                        .collect();
+                    sums.sort_by(|a, b| a.total_cmp(b));
+                    sums
+                })
+                .collect();
+            settings
+                .levels
+                .iter()
+                .map(|&level| Band {
+                    level,
+                    lower: point
+                        .iter()
+                        .zip(paths.iter())
+                        .map(|(x, sums)| x + quantile(sums, (1. - level) / 2.))
+                        .collect(),
+                    upper: point
+                        .iter()
+                        .zip(paths.iter())
+                        .map(|(x, sums)| x + quantile(sums, 0.5 + level / 2.))
+                        .collect(),
+                })
+                .collect()
+        }
+    };
+    Intervals { start, bands }
+}
+
+/// Actual minus forecast for every step ahead, refitted from each origin in the second half of the series.
+pub fn step_errors(model: &dyn ForecastModel, demand: &[f64], horizon: usize) -> Vec<Vec<f64>> {
+    let mut errors = vec![vec![]; horizon];
+    let first = (demand.len() / 2).max(model.min_fit_len()).max(2);
+    for origin in first..demand.len() {
+        let Ok(forecast) = model.forecast(&demand[..origin], horizon.max(1)) else {
+            continue;
+        };
+        for h in 0..horizon {
+            if origin + h < demand.len() && forecast[origin + h].is_finite() {
+                errors[h].push(demand[origin + h] - forecast[origin + h]);
+            }
+        }
+    }
+    errors
+}
+
+fn rms(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.;
+    }
+    (values.iter().map(|x| x * x).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.;
+    }
+    let position = p.clamp(0., 1.) * (sorted.len() - 1) as f64;
+    let low = position.floor() as usize;
+    let high = position.ceil() as usize;
+    sorted[low] + (sorted[high] - sorted[low]) * (position - low as f64)
+}
+
+/// Acklam's rational approximation, accurate to about 1e-9.
+pub fn inverse_normal(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let p = p.clamp(1e-12, 1. - 1e-12);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    };
+    if p < 0.02425 {
+        tail((-2. * p.ln()).sqrt())
+    } else if p > 1. - 0.02425 {
+        -tail((-2. * (1. - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.)
+    }
+}
+
+/// xorshift, fixed seeds keep the bands stable between frames.
+struct Random(u64);
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
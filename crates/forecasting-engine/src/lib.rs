@@ -0,0 +1,25 @@
+//! Forecasting methods without any UI, shared by the forecasting app and the batch CLI.
+
+pub mod arima;
+pub mod ets;
+pub mod intermittent;
+pub mod intervals;
+pub mod model;
+pub mod models;
+pub mod optimise;
+pub mod result;
+pub mod seasonality;
+pub mod smoothing;
+pub mod validation;
+
+pub use model::{ForecastModel, Kind, Parameter};
+pub use result::ForecastResult;
+
+pub fn mean(data: &[f64]) -> f64 {
+    let sum: f64 = data.iter().sum();
+    sum / data.len() as f64
+}
+
+pub fn lin_smooth(alpha: f64, a: f64, b: f64) -> f64 {
+    alpha * a + (1. - alpha) * b
+}
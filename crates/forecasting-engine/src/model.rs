@@ -0,0 +1,135 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// Window and season lengths, at least 1.
+    Integer,
+    /// Smoothing factors between 0 and 1.
+    Unit,
+    /// ARIMA orders, 0 to `MAX_ORDER`.
+    Order,
+    /// Index into the named variants.
+    Choice(&'static [&'static str]),
+}
+
+pub const MAX_ORDER: usize = 2;
+
+pub const SEASON_LENGTH: &str = "season length";
+/// Weekly data has a yearly period of 52.
+pub const MAX_SEASON_LEN: usize = 52;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub kind: Kind,
+}
+
+impl Parameter {
+    pub const fn integer(name: &'static str) -> Self {
+        Self {
+            name,
+            kind: Kind::Integer,
+        }
+    }
+
+    pub const fn unit(name: &'static str) -> Self {
+        Self {
+            name,
+            kind: Kind::Unit,
+        }
+    }
+
+    pub const fn order(name: &'static str) -> Self {
+        Self {
+            name,
+            kind: Kind::Order,
+        }
+    }
+
+    pub const fn choice(name: &'static str, variants: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            kind: Kind::Choice(variants),
+        }
+    }
+
+    pub fn clamp(&self, value: f64) -> f64 {
+        match self.kind {
+            Kind::Integer => value.round().max(1.),
+            Kind::Unit => value.clamp(0., 1.),
+            Kind::Order => value.round().clamp(0., MAX_ORDER as f64),
+            Kind::Choice(variants) => value.round().clamp(0., variants.len().saturating_sub(1) as f64),
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        match self.kind {
+            Kind::Integer | Kind::Order => format!("{} {}", self.name, value),
+            Kind::Unit => format!("{} {:.3}", self.name, value),
+            Kind::Choice(variants) => variants.get(value as usize).unwrap_or(&"?").to_string(),
+        }
+    }
+}
+
+/// A forecasting method with its parameters, `get` and `set` address them by their index in `parameters`.
+pub trait ForecastModel: Send + Sync {
+    fn parameters(&self) -> &'static [Parameter];
+    fn get(&self, index: usize) -> f64;
+    fn set(&mut self, index: usize, value: f64);
+    /// Takes the demand and estimates whatever the model learns from it, e.g. ARIMA coefficients.
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String>;
+    /// One step ahead forecasts for the fitted demand, NaN where the method has no history yet,
+    /// followed by `extra_periods` forecasts into the future.
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String>;
+    fn clone_box(&self) -> Box<dyn ForecastModel>;
+
+    /// Periods `fit` needs at least, e.g. the window of a moving average.
+    /// A season length only counts for models that use the season.
+    fn min_fit_len(&self) -> usize {
+        1
+    }
+
+    /// `fit` and `predict` on a copy, the parameters stay as they are.
+    fn forecast(&self, demand: &[f64], extra_periods: usize) -> Result<Vec<f64>, String> {
+        let mut model = self.clone_box();
+        model.fit(demand)?;
+        model.predict(extra_periods)
+    }
+
+    fn describe(&self) -> String {
+        self.parameters()
+            .iter()
+            .enumerate()
+            .map(|(i, parameter)| parameter.format(self.get(i)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Clone for Box<dyn ForecastModel> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Sets the season length of models that have one, returns whether there was one.
+pub fn set_season_len(model: &mut dyn ForecastModel, season_len: usize) -> bool {
+    let Some(index) = model.parameters().iter().position(|x| x.name == SEASON_LENGTH) else {
+        return false;
+    };
+    model.set(index, season_len as f64);
+    true
+}
+
+/// The error for series a model cannot be fitted to.
+pub fn too_short(demand: &[f64], min_len: usize) -> Result<(), String> {
+    if demand.len() < min_len {
+        return Err(format!(
+            "{} periods are too few, at least {} are needed",
+            demand.len(),
+            min_len
+        ));
+    }
+    if demand.iter().any(|x| !x.is_finite()) {
+        return Err("demand contains missing values".to_string());
+    }
+    Ok(())
+}
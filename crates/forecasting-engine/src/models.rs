@@ -0,0 +1,722 @@
+use crate::arima::{self, Order};
+use crate::ets::{Ets, Seasonality, Trend};
+use crate::model::{ForecastModel, Parameter, SEASON_LENGTH, too_short};
+
+fn fitted(demand: &Option<Vec<f64>>) -> Result<&[f64], String> {
+    demand
+        .as_deref()
+        .ok_or_else(|| "the model has not been fitted".to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct MovingAverage {
+    pub n: usize,
+    demand: Option<Vec<f64>>,
+}
+
+impl MovingAverage {
+    pub fn new(n: usize) -> Self {
+        Self { n, demand: None }
+    }
+}
+
+impl ForecastModel for MovingAverage {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 1] = [Parameter::integer("n")];
+        &PARAMETERS
+    }
+
+    fn get(&self, _index: usize) -> f64 {
+        self.n as f64
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        self.n = self.parameters()[index].clamp(value) as usize;
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, self.min_fit_len())?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(crate::smoothing::moving_average(demand, extra_periods, self.n.max(1)))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+
+    fn min_fit_len(&self) -> usize {
+        self.n.max(1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpSmooth {
+    pub alpha: f64,
+    demand: Option<Vec<f64>>,
+}
+
+impl ExpSmooth {
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha, demand: None }
+    }
+}
+
+impl ForecastModel for ExpSmooth {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 1] = [Parameter::unit("alpha")];
+        &PARAMETERS
+    }
+
+    fn get(&self, _index: usize) -> f64 {
+        self.alpha
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        self.alpha = self.parameters()[index].clamp(value);
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 1)?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(crate::smoothing::exp_smooth(demand, extra_periods, self.alpha))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoubleExpSmooth {
+    pub alpha: f64,
+    pub beta: f64,
+    demand: Option<Vec<f64>>,
+}
+
+impl DoubleExpSmooth {
+    pub fn new(alpha: f64, beta: f64) -> Self {
+        Self {
+            alpha,
+            beta,
+            demand: None,
+        }
+    }
+}
+
+impl ForecastModel for DoubleExpSmooth {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 2] = [Parameter::unit("alpha"), Parameter::unit("beta")];
+        &PARAMETERS
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        [self.alpha, self.beta][index]
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let value = self.parameters()[index].clamp(value);
+        match index {
+            0 => self.alpha = value,
+            _ => self.beta = value,
+        }
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 2)?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(crate::smoothing::double_exp_smooth(demand, extra_periods, self.alpha, self.beta))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoubleExpSmoothDamped {
+    pub alpha: f64,
+    pub beta: f64,
+    pub phi: f64,
+    demand: Option<Vec<f64>>,
+}
+
+impl DoubleExpSmoothDamped {
+    pub fn new(alpha: f64, beta: f64, phi: f64) -> Self {
+        Self {
+            alpha,
+            beta,
+            phi,
+            demand: None,
+        }
+    }
+}
+
+impl ForecastModel for DoubleExpSmoothDamped {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 3] = [
+            Parameter::unit("alpha"),
+            Parameter::unit("beta"),
+            Parameter::unit("phi"),
+        ];
+        &PARAMETERS
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        [self.alpha, self.beta, self.phi][index]
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let value = self.parameters()[index].clamp(value);
+        match index {
+            0 => self.alpha = value,
+            1 => self.beta = value,
+            _ => self.phi = value,
+        }
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 2)?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(crate::smoothing::double_exp_smooth_damped(
+            demand,
+            extra_periods,
+            self.alpha,
+            self.beta,
+            self.phi,
+        ))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+}
+
+const TRENDS: [&str; 3] = ["no trend", "additive trend", "damped trend"];
+const SEASONALITIES: [&str; 3] = ["no seasonality", "additive seasonality", "multiplicative seasonality"];
+
+/// Holt-Winters in all its ETS variants, trend and seasonality are parameters like the factors.
+#[derive(Debug, Clone)]
+pub struct HoltWinters {
+    pub ets: Ets,
+    demand: Option<Vec<f64>>,
+}
+
+impl HoltWinters {
+    pub fn new(ets: Ets) -> Self {
+        Self { ets, demand: None }
+    }
+}
+
+impl ForecastModel for HoltWinters {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 7] = [
+            Parameter::choice("trend", &TRENDS),
+            Parameter::choice("seasonality", &SEASONALITIES),
+            Parameter::integer(SEASON_LENGTH),
+            Parameter::unit("alpha"),
+            Parameter::unit("beta"),
+            Parameter::unit("gamma"),
+            Parameter::unit("phi"),
+        ];
+        &PARAMETERS
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        let e = &self.ets;
+        let trend = Trend::ALL.iter().position(|&x| x == e.trend).unwrap_or(0);
+        let seasonality = Seasonality::ALL.iter().position(|&x| x == e.seasonality).unwrap_or(0);
+        [
+            trend as f64,
+            seasonality as f64,
+            e.season_len as f64,
+            e.alpha,
+            e.beta,
+            e.gamma,
+            e.phi,
+        ][index]
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let value = self.parameters()[index].clamp(value);
+        let e = &mut self.ets;
+        match index {
+            0 => e.trend = Trend::ALL[value as usize],
+            1 => e.seasonality = Seasonality::ALL[value as usize],
+            2 => e.season_len = value as usize,
+            3 => e.alpha = value,
+            4 => e.beta = value,
+            5 => e.gamma = value,
+            _ => e.phi = value,
+        }
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, self.min_fit_len())?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(self.ets.forecast(demand, extra_periods))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+
+    fn min_fit_len(&self) -> usize {
+        self.ets.min_len()
+    }
+}
+
+/// Croston's method, or the Syntetos–Boylan approximation of it with `sba`.
+#[derive(Debug, Clone)]
+pub struct Croston {
+    pub alpha: f64,
+    pub sba: bool,
+    demand: Option<Vec<f64>>,
+}
+
+impl Croston {
+    pub fn new(alpha: f64, sba: bool) -> Self {
+        Self {
+            alpha,
+            sba,
+            demand: None,
+        }
+    }
+}
+
+impl ForecastModel for Croston {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 1] = [Parameter::unit("alpha")];
+        &PARAMETERS
+    }
+
+    fn get(&self, _index: usize) -> f64 {
+        self.alpha
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        self.alpha = self.parameters()[index].clamp(value);
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 1)?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(crate::intermittent::croston(demand, extra_periods, self.alpha, self.sba))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Tsb {
+    pub alpha: f64,
+    pub beta: f64,
+    demand: Option<Vec<f64>>,
+}
+
+impl Tsb {
+    pub fn new(alpha: f64, beta: f64) -> Self {
+        Self {
+            alpha,
+            beta,
+            demand: None,
+        }
+    }
+}
+
+impl ForecastModel for Tsb {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 2] = [Parameter::unit("alpha"), Parameter::unit("beta")];
+        &PARAMETERS
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        [self.alpha, self.beta][index]
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let value = self.parameters()[index].clamp(value);
+        match index {
+            0 => self.alpha = value,
+            _ => self.beta = value,
+        }
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 1)?;
+        self.demand = Some(demand.to_vec());
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let demand = fitted(&self.demand)?;
+        Ok(crate::intermittent::tsb(demand, extra_periods, self.alpha, self.beta))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Arima {
+    pub order: Order,
+    fitted: Option<(Vec<f64>, arima::Model)>,
+}
+
+impl Arima {
+    pub fn new(order: Order) -> Self {
+        Self { order, fitted: None }
+    }
+}
+
+impl ForecastModel for Arima {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 7] = [
+            Parameter::order("p"),
+            Parameter::order("d"),
+            Parameter::order("q"),
+            Parameter::order("seasonal p"),
+            Parameter::order("seasonal d"),
+            Parameter::order("seasonal q"),
+            Parameter::integer(SEASON_LENGTH),
+        ];
+        &PARAMETERS
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        let o = &self.order;
+        [o.p, o.d, o.q, o.seasonal_p, o.seasonal_d, o.seasonal_q, o.season_len][index] as f64
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        let value = self.parameters()[index].clamp(value) as usize;
+        let o = &mut self.order;
+        match index {
+            0 => o.p = value,
+            1 => o.d = value,
+            2 => o.q = value,
+            3 => o.seasonal_p = value,
+            4 => o.seasonal_d = value,
+            5 => o.seasonal_q = value,
+            _ => o.season_len = value,
+        }
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 1)?;
+        self.fitted = Some((demand.to_vec(), arima::fit(demand, self.order)?));
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let (demand, model) = self
+            .fitted
+            .as_ref()
+            .ok_or_else(|| "the model has not been fitted".to_string())?;
+        Ok(model.forecast(demand, extra_periods))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+}
+
+/// Orders are picked by AIC on every fit, only the season length is up to the user.
+#[derive(Debug, Clone)]
+pub struct AutoArima {
+    pub season_len: usize,
+    fitted: Option<(Vec<f64>, arima::Model)>,
+}
+
+impl AutoArima {
+    pub fn new(season_len: usize) -> Self {
+        Self {
+            season_len,
+            fitted: None,
+        }
+    }
+
+    /// The order picked by the last fit.
+    pub fn order(&self) -> Option<Order> {
+        self.fitted.as_ref().map(|x| x.1.order)
+    }
+}
+
+impl ForecastModel for AutoArima {
+    fn parameters(&self) -> &'static [Parameter] {
+        const PARAMETERS: [Parameter; 1] = [Parameter::integer(SEASON_LENGTH)];
+        &PARAMETERS
+    }
+
+    fn get(&self, _index: usize) -> f64 {
+        self.season_len as f64
+    }
+
+    fn set(&mut self, index: usize, value: f64) {
+        self.season_len = self.parameters()[index].clamp(value) as usize;
+    }
+
+    fn fit(&mut self, demand: &[f64]) -> Result<(), String> {
+        too_short(demand, 1)?;
+        self.fitted = Some((demand.to_vec(), arima::auto(demand, self.season_len)?));
+        Ok(())
+    }
+
+    fn predict(&self, extra_periods: usize) -> Result<Vec<f64>, String> {
+        let (demand, model) = self
+            .fitted
+            .as_ref()
+            .ok_or_else(|| "the model has not been fitted".to_string())?;
+        Ok(model.forecast(demand, extra_periods))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForecastModel> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        match self.order() {
+            Some(order) => format!("{} {}, ARIMA{}", SEASON_LENGTH, self.season_len, order.describe()),
+            None => format!("{} {}", SEASON_LENGTH, self.season_len),
+        }
+    }
+}
+
+/// Every method with the parameters the app starts with.
+pub fn defaults() -> Vec<(&'static str, Box<dyn ForecastModel>)> {
+    vec![
+        ("moving average", Box::new(MovingAverage::new(5))),
+        ("exp smooth", Box::new(ExpSmooth::new(0.4))),
+        ("double exp smooth", Box::new(DoubleExpSmooth::new(0.4, 0.4))),
+        ("double exp smooth damped", Box::new(DoubleExpSmoothDamped::new(0.4, 0.4, 0.9))),
+        (
+            "holt winters",
+            Box::new(HoltWinters::new(Ets {
+                trend: Trend::Damped,
+                seasonality: Seasonality::Multiplicative,
+                season_len: 12,
+                alpha: 0.4,
+                beta: 0.4,
+                gamma: 0.3,
+                phi: 0.9,
+            })),
+        ),
+        ("croston", Box::new(Croston::new(0.1, false))),
+        ("sba", Box::new(Croston::new(0.1, true))),
+        ("tsb", Box::new(Tsb::new(0.1, 0.1))),
+        (
+            "arima",
+            Box::new(Arima::new(Order {
+                p: 1,
+                d: 1,
+                q: 1,
+                season_len: 12,
+                ..Default::default()
+            })),
+        ),
+        (
+            "sarima",
+            Box::new(Arima::new(Order {
+                p: 0,
+                d: 1,
+                q: 1,
+                seasonal_p: 0,
+                seasonal_d: 1,
+                seasonal_q: 1,
+                season_len: 12,
+            })),
+        ),
+        ("auto arima", Box::new(AutoArima::new(12))),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAMPOO: [f64; 36] = [
+        266.0, 145.9, 183.1, 119.3, 180.3, 168.5, 231.8, 224.5, 192.8, 122.9, 336.5, 185.9, 194.3, 149.5,
+        210.1, 273.3, 191.4, 287.0, 226.0, 303.6, 289.9, 421.6, 264.5, 342.3, 339.7, 440.4, 315.9, 439.3,
+        401.3, 437.4, 575.5, 407.6, 682.0, 475.3, 581.3, 646.9,
+    ];
+
+    fn all() -> Vec<Box<dyn ForecastModel>> {
+        vec![
+            Box::new(MovingAverage::new(3)),
+            Box::new(ExpSmooth::new(0.4)),
+            Box::new(DoubleExpSmooth::new(0.4, 0.4)),
+            Box::new(DoubleExpSmoothDamped::new(0.4, 0.4, 0.9)),
+            Box::new(HoltWinters::new(Ets {
+                trend: Trend::Damped,
+                seasonality: Seasonality::Multiplicative,
+                season_len: 12,
+                alpha: 0.4,
+                beta: 0.4,
+                gamma: 0.3,
+                phi: 0.9,
+            })),
+            Box::new(Croston::new(0.1, false)),
+            Box::new(Tsb::new(0.1, 0.1)),
+            Box::new(Arima::new(Order {
+                p: 1,
+                d: 1,
+                q: 1,
+                ..Default::default()
+            })),
+            Box::new(AutoArima::new(1)),
+        ]
+    }
+
+    #[test]
+    fn forecast_covers_demand_and_extra_periods() {
+        for model in all() {
+            let forecast = model.forecast(&SHAMPOO, 5).unwrap();
+            assert_eq!(forecast.len(), SHAMPOO.len() + 5, "{}", model.describe());
+            assert!(forecast[SHAMPOO.len()..].iter().all(|x| x.is_finite()), "{}", model.describe());
+        }
+    }
+
+    #[test]
+    fn too_short_series_is_an_error() {
+        for model in all() {
+            assert!(model.forecast(&[], 3).is_err(), "{}", model.describe());
+        }
+        assert!(MovingAverage::new(5).forecast(&SHAMPOO[..4], 3).is_err());
+        assert!(DoubleExpSmooth::new(0.4, 0.4).forecast(&SHAMPOO[..1], 3).is_err());
+        assert!(all()[4].forecast(&SHAMPOO[..23], 3).is_err());
+        assert!(all()[7].forecast(&SHAMPOO[..3], 3).is_err());
+    }
+
+    #[test]
+    fn season_length_only_counts_for_seasonal_models() {
+        let settings = crate::validation::ValidationSettings {
+            test_len: 12,
+            ..Default::default()
+        };
+        let mut model = HoltWinters::new(Ets {
+            trend: Trend::Additive,
+            seasonality: Seasonality::None,
+            season_len: 52,
+            alpha: 0.4,
+            beta: 0.4,
+            gamma: 0.3,
+            phi: 0.9,
+        });
+        assert_eq!(model.min_fit_len(), 2);
+        assert!(crate::validation::validate(&model, &SHAMPOO, &settings).is_ok());
+        model.ets.seasonality = Seasonality::Additive;
+        assert!(crate::validation::validate(&model, &SHAMPOO, &settings).is_err());
+        assert!(crate::validation::validate(&AutoArima::new(52), &SHAMPOO, &settings).is_ok());
+        assert!(crate::validation::validate(&MovingAverage::new(30), &SHAMPOO, &settings).is_err());
+    }
+
+    #[test]
+    fn missing_values_are_an_error() {
+        assert!(ExpSmooth::new(0.4).forecast(&[1., f64::NAN, 2.], 1).is_err());
+    }
+
+    #[test]
+    fn predict_needs_fit() {
+        assert!(ExpSmooth::new(0.4).predict(3).is_err());
+        let mut model = ExpSmooth::new(0.4);
+        model.fit(&[10., 20.]).unwrap();
+        // 10 for period 1, then 0.4 * 20 + 0.6 * 10
+        assert_eq!(model.predict(2).unwrap()[1..], [10., 14., 14.]);
+    }
+
+    #[test]
+    fn moving_average_values() {
+        let forecast = MovingAverage::new(2).forecast(&[1., 3., 5., 7.], 2).unwrap();
+        assert!(forecast[..2].iter().all(|x| x.is_nan()));
+        assert_eq!(forecast[2..], [2., 4., 6., 6.]);
+    }
+
+    #[test]
+    fn exp_smooth_without_extra_periods() {
+        let forecast = ExpSmooth::new(0.5).forecast(&[2., 4., 6.], 0).unwrap();
+        assert_eq!(forecast[1..], [2., 3.]);
+    }
+
+    #[test]
+    fn double_exp_smooth_follows_a_line() {
+        let demand: Vec<f64> = (0..10).map(|x| 5. + 2. * x as f64).collect();
+        let forecast = DoubleExpSmooth::new(0.3, 0.2).forecast(&demand, 3).unwrap();
+        for (i, x) in forecast.iter().enumerate().skip(1) {
+            assert!((x - (5. + 2. * i as f64)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn croston_without_demand_is_zero() {
+        let forecast = Croston::new(0.1, true).forecast(&[0., 0., 0.], 2).unwrap();
+        assert!(forecast.iter().all(|&x| x == 0.));
+    }
+
+    #[test]
+    fn sba_is_below_croston() {
+        let demand = [0., 3., 0., 0., 5., 0., 2., 0., 0., 4.];
+        let croston = Croston::new(0.2, false).forecast(&demand, 1).unwrap();
+        let sba = Croston::new(0.2, true).forecast(&demand, 1).unwrap();
+        assert!((sba[10] - croston[10] * 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arima_recovers_ar_coefficient() {
+        let mut state = 7u64;
+        let mut demand = vec![10.];
+        for _ in 0..400 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let noise = (state % 1000) as f64 / 1000. - 0.5;
+            demand.push(4. + 0.6 * demand.last().unwrap() + noise);
+        }
+        let mut model = Arima::new(Order {
+            p: 1,
+            ..Default::default()
+        });
+        model.fit(&demand).unwrap();
+        let (_, fitted) = model.fitted.as_ref().unwrap();
+        assert!((-fitted.ar[1] - 0.6).abs() < 0.1, "{:?}", fitted.ar);
+    }
+
+    #[test]
+    fn describe_names_choices() {
+        assert_eq!(
+            all()[4].describe(),
+            "damped trend, multiplicative seasonality, season length 12, alpha 0.400, beta 0.400, gamma 0.300, phi 0.900"
+        );
+    }
+}
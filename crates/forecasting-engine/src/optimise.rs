@@ -0,0 +1,238 @@
+use crate::ForecastResult;
+use crate::model::{ForecastModel, Kind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Mae,
+    Rmse,
+    Mape,
+    Mase,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [Metric::Mae, Metric::Rmse, Metric::Mape, Metric::Mase];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Mae => "MAE",
+            Metric::Rmse => "RMSE",
+            Metric::Mape => "MAPE",
+            Metric::Mase => "MASE",
+        }
+    }
+
+    /// NaN scores, e.g. from a window longer than the series, never win.
+    pub fn score(&self, result: &ForecastResult) -> f64 {
+        let score = match self {
+            Metric::Mae => result.mae,
+            Metric::Rmse => result.rmse,
+            Metric::Mape => result.mape,
+            Metric::Mase => result.mase,
+        };
+        if score.is_finite() { score } else { f64::INFINITY }
+    }
+
+    /// The same for the errors on a held out test window.
+    pub fn score_errors(&self, errors: &crate::validation::Errors) -> f64 {
+        let score = match self {
+            Metric::Mae => errors.mae,
+            Metric::Rmse => errors.rmse,
+            Metric::Mape => errors.mape,
+            Metric::Mase => errors.mase,
+        };
+        if score.is_finite() { score } else { f64::INFINITY }
+    }
+}
+
+#[derive(Clone)]
+pub struct Fit {
+    pub model: Box<dyn ForecastModel>,
+    pub metric: Metric,
+    pub score: f64,
+}
+
+impl Fit {
+    pub fn describe(&self) -> String {
+        format!("{} {:.3} with {}", self.metric.name(), self.score, self.model.describe())
+    }
+}
+
+const GRID_STEPS: usize = 5;
+
+/// Grid search over every parameter of the method, then Nelder-Mead on the continuous ones from the best grid point.
+pub fn optimise(
+    start: &dyn ForecastModel,
+    demand: &[f64],
+    extra_periods: usize,
+    metric: Metric,
+) -> Fit {
+    let evaluate = |model: &dyn ForecastModel| match model.forecast(demand, extra_periods) {
+        Ok(forecast) => metric.score(&ForecastResult::new(forecast, demand)),
+        Err(_) => f64::INFINITY,
+    };
+
+    let specs = start.parameters();
+    let discrete: Vec<usize> = (0..specs.len()).filter(|&i| specs[i].kind != Kind::Unit).collect();
+    let continuous: Vec<usize> = (0..specs.len()).filter(|&i| specs[i].kind == Kind::Unit).collect();
+
+    let mut best = Fit {
+        model: start.clone_box(),
+        metric,
+        score: evaluate(start),
+    };
+
+    // same range as the sliders, but windows longer than half the series leave nothing to score
+    let max_integer = (demand.len() / 2).clamp(1, 12);
+    let integer_range: Vec<f64> = (1..=max_integer).map(|x| x as f64).collect();
+    // 2^k points for k orders already, higher orders are left to the sliders
+    let order_range: Vec<f64> = vec![0., 1.];
+    let grid: Vec<f64> = (0..=GRID_STEPS).map(|i| i as f64 / GRID_STEPS as f64).collect();
+    let mut candidate = start.clone_box();
+    let discrete_axes: Vec<_> = discrete
+        .iter()
+        .map(|&i| match specs[i].kind {
+            Kind::Order => order_range.clone(),
+            Kind::Choice(variants) => (0..variants.len()).map(|x| x as f64).collect(),
+            _ => integer_range.clone(),
+        })
+        .collect();
+    for_each_combination(&discrete_axes, &mut |ints| {
+        for (&index, value) in discrete.iter().zip(ints) {
+            candidate.set(index, *value);
+        }
+        for_each_combination(&continuous.iter().map(|_| grid.clone()).collect::<Vec<_>>(), &mut |values| {
+            for (&index, value) in continuous.iter().zip(values) {
+                candidate.set(index, *value);
+            }
+            let score = evaluate(candidate.as_ref());
+            if score < best.score {
+                best.model = candidate.clone();
+                best.score = score;
+            }
+        });
+    });
+
+    if !continuous.is_empty() {
+        let from: Vec<_> = continuous.iter().map(|&i| best.model.get(i)).collect();
+        let base = best.model.clone();
+        let (values, score) = nelder_mead(
+            |values| {
+                let mut model = base.clone();
+                for (&index, value) in continuous.iter().zip(values) {
+                    model.set(index, *value);
+                }
+                evaluate(model.as_ref())
+            },
+            from,
+            (0., 1.),
+            200,
+        );
+        if score < best.score {
+            for (&index, value) in continuous.iter().zip(values.iter()) {
+                best.model.set(index, *value);
+            }
+            best.score = score;
+        }
+    }
+
+    best
+}
+
+fn for_each_combination(axes: &[Vec<f64>], f: &mut impl FnMut(&[f64])) {
+    fn recurse(axes: &[Vec<f64>], current: &mut Vec<f64>, f: &mut impl FnMut(&[f64])) {
+        match axes.split_first() {
+            None => f(current),
+            Some((axis, rest)) => {
+                for value in axis.iter() {
+                    current.push(*value);
+                    recurse(rest, current, f);
+                    current.pop();
+                }
+            }
+        }
+    }
+    recurse(axes, &mut vec![], f);
+}
+
+/// Derivative free minimisation, points are clamped to `bounds` in every dimension.
+pub fn nelder_mead(
+    f: impl Fn(&[f64]) -> f64,
+    start: Vec<f64>,
+    bounds: (f64, f64),
+    iterations: usize,
+) -> (Vec<f64>, f64) {
+    let clamp = |x: Vec<f64>| x.into_iter().map(|v| v.clamp(bounds.0, bounds.1)).collect::<Vec<_>>();
+    let dimensions = start.len();
+
+    let mut simplex = vec![clamp(start.clone())];
+    for i in 0..dimensions {
+        let mut point = start.clone();
+        point[i] += if point[i] + 0.1 <= bounds.1 { 0.1 } else { -0.1 };
+        simplex.push(clamp(point));
+    }
+    let mut scores: Vec<f64> = simplex.iter().map(|x| f(x)).collect();
+
+    for _ in 0..iterations {
+        let mut order: Vec<usize> = (0..simplex.len()).collect();
+        order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+        simplex = order.iter().map(|&i| simplex[i].clone()).collect();
+        scores = order.iter().map(|&i| scores[i]).collect();
+
+        if (scores[dimensions] - scores[0]).abs() < 1e-10 {
+            break;
+        }
+
+        let centroid: Vec<f64> = (0..dimensions)
+            .map(|d| simplex[..dimensions].iter().map(|x| x[d]).sum::<f64>() / dimensions as f64)
+            .collect();
+        let towards = |factor: f64| {
+            clamp(
+                centroid
+                    .iter()
+                    .zip(simplex[dimensions].iter())
+                    .map(|(c, w)| c + factor * (c - w))
+                    .collect(),
+            )
+        };
+
+        let reflected = towards(1.);
+        let reflected_score = f(&reflected);
+        if reflected_score < scores[0] {
+            let expanded = towards(2.);
+            let expanded_score = f(&expanded);
+            if expanded_score < reflected_score {
+                simplex[dimensions] = expanded;
+                scores[dimensions] = expanded_score;
+            } else {
+                simplex[dimensions] = reflected;
+                scores[dimensions] = reflected_score;
+            }
+        } else if reflected_score < scores[dimensions - 1] {
+            simplex[dimensions] = reflected;
+            scores[dimensions] = reflected_score;
+        } else {
+            let contracted = towards(-0.5);
+            let contracted_score = f(&contracted);
+            if contracted_score < scores[dimensions] {
+                simplex[dimensions] = contracted;
+                scores[dimensions] = contracted_score;
+            } else {
+                for i in 1..simplex.len() {
+                    simplex[i] = clamp(
+                        simplex[0]
+                            .iter()
+                            .zip(simplex[i].iter())
+                            .map(|(b, x)| b + 0.5 * (x - b))
+                            .collect(),
+                    );
+                    scores[i] = f(&simplex[i]);
+                }
+            }
+        }
+    }
+
+    let best = (0..simplex.len())
+        .min_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+        .unwrap_or(0);
+    (simplex[best].clone(), scores[best])
+}
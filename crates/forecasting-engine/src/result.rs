@@ -0,0 +1,63 @@
+use crate::{intermittent, mean};
+
+/// In-sample error metrics of a forecast, periods without a forecast are left out.
+pub struct ForecastResult {
+    pub forecast: Vec<f64>,
+    pub err: Vec<f64>,
+    /// Divides `err` into scaled errors, the in-sample MAE of the naive forecast.
+    pub scale: f64,
+    pub mase: f64,
+    pub bias_abs: f64,
+    pub bias_rel: f64,
+    pub mape: f64,
+    pub mae: f64,
+    pub mae_rel: f64,
+    pub rmse: f64,
+    pub rmse_rel: f64,
+}
+
+impl ForecastResult {
+    pub fn new(forecast: Vec<f64>, demand: &[f64]) -> Self {
+        let mut err = vec![];
+        for i in 0..forecast.len().min(demand.len()) {
+            err.push(forecast[i] - demand[i]);
+        }
+        let results: Vec<_> = demand
+            .iter()
+            .copied()
+            .zip(err.clone())
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .collect();
+
+        let demad: Vec<_> = results.iter().map(|x| x.0).collect();
+        let err: Vec<_> = results.iter().map(|x| x.1).collect();
+
+        // periods without demand have no percentage error
+        let relative: Vec<_> = results.iter().filter(|x| x.0 != 0.).map(|x| x.1.abs() / x.0.abs()).collect();
+        let mape = mean(&relative);
+
+        let mae = results.iter().map(|x| x.1.abs()).sum::<f64>() / results.len() as f64;
+
+        let rmse = (results.iter().map(|x| x.1 * x.1).sum::<f64>() / results.len() as f64).sqrt();
+
+        let dem_ave = mean(&demad);
+        let bias_abs = mean(&err);
+        let bias_rel = bias_abs / dem_ave;
+        let mae_rel = mae / dem_ave;
+        let rmse_rel = rmse / dem_ave;
+        let scale = intermittent::naive_scale(demand);
+        Self {
+            forecast,
+            err,
+            scale,
+            mase: mae / scale,
+            bias_rel,
+            bias_abs,
+            mape,
+            mae,
+            mae_rel,
+            rmse,
+            rmse_rel,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+#[derive(Debug, Clone, Copy)]
+pub struct Period {
+    pub len: usize,
+    /// Autocorrelation of the detrended series at `len`.
+    pub acf: f64,
+}
+
+/// Autocorrelations for lags 0 to `max_lag`.
+pub fn acf(data: &[f64], max_lag: usize) -> Vec<f64> {
+    let mean = crate::mean(data);
+    let variance: f64 = data.iter().map(|x| (x - mean).powi(2)).sum();
+    (0..=max_lag.min(data.len().saturating_sub(1)))
+        .map(|lag| {
+            let covariance: f64 = (lag..data.len()).map(|i| (data[i] - mean) * (data[i - lag] - mean)).sum();
+            if variance > 0. { covariance / variance } else { 0. }
+        })
+        .collect()
+}
+
+/// Residuals of the least squares line, a trend would otherwise dominate every lag.
+fn detrend(data: &[f64]) -> Vec<f64> {
+    let n = data.len() as f64;
+    let x_mean = (n - 1.) / 2.;
+    let y_mean = crate::mean(data);
+    let sxy: f64 = data.iter().enumerate().map(|(i, y)| (i as f64 - x_mean) * (y - y_mean)).sum();
+    let sxx: f64 = (0..data.len()).map(|i| (i as f64 - x_mean).powi(2)).sum();
+    let slope = if sxx > 0. { sxy / sxx } else { 0. };
+    data.iter()
+        .enumerate()
+        .map(|(i, y)| y - y_mean - slope * (i as f64 - x_mean))
+        .collect()
+}
+
+/// The highest local peak of the autocorrelation that is significant at about 95 %, with at least
+/// two full cycles in the series.
+pub fn detect(demand: &[f64]) -> Option<Period> {
+    if demand.len() < 8 {
+        return None;
+    }
+    let acf = acf(&detrend(demand), demand.len() / 2);
+    let threshold = 2. / (demand.len() as f64).sqrt();
+    (2..acf.len().saturating_sub(1))
+        .filter(|&lag| acf[lag] > acf[lag - 1] && acf[lag] >= acf[lag + 1] && acf[lag] > threshold)
+        .map(|lag| Period { len: lag, acf: acf[lag] })
+        // multiples of the period peak as well, prefer the shortest one that is nearly as strong
+        .fold(None, |best: Option<Period>, x| match best {
+            Some(best) if x.acf <= best.acf * 1.1 => Some(best),
+            _ => Some(x),
+        })
+}
+
+pub struct Decomposition {
+    pub trend: Vec<f64>,
+    pub seasonal: Vec<f64>,
+    pub remainder: Vec<f64>,
+}
+
+/// Moving average of `window` periods, shrunk to the available ones at the ends.
+fn centred_average(data: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+    (0..data.len())
+        .map(|i| {
+            let from = i.saturating_sub(half);
+            let to = (i + half + 1).min(data.len());
+            if window.is_multiple_of(2) && from + half == i && to == i + half + 1 {
+                // 2 x m average for an even window, the outer points count half
+                let inner: f64 = data[from + 1..to - 1].iter().sum();
+                (inner + (data[from] + data[to - 1]) / 2.) / window as f64
+            } else {
+                crate::mean(&data[from..to])
+            }
+        })
+        .collect()
+}
+
+/// Additive STL-style decomposition: the trend is a centred moving average over one period, the
+/// seasonal component the mean of each cycle subseries of the detrended data, both refined in turns.
+pub fn decompose(demand: &[f64], period: usize) -> Decomposition {
+    let period = period.clamp(1, demand.len().max(1));
+    let mut seasonal = vec![0.; demand.len()];
+    let mut trend = vec![0.; demand.len()];
+    for _ in 0..3 {
+        let deseasonalised: Vec<f64> = demand.iter().zip(seasonal.iter()).map(|(d, s)| d - s).collect();
+        trend = centred_average(&deseasonalised, period);
+        let detrended: Vec<f64> = demand.iter().zip(trend.iter()).map(|(d, t)| d - t).collect();
+        let mut cycle: Vec<f64> = (0..period)
+            .map(|i| crate::mean(&detrended.iter().skip(i).step_by(period).copied().collect::<Vec<_>>()))
+            .collect();
+        let level = crate::mean(&cycle);
+        cycle.iter_mut().for_each(|x| *x -= level);
+        seasonal = (0..demand.len()).map(|i| cycle[i % period]).collect();
+    }
+    let remainder = (0..demand.len()).map(|i| demand[i] - trend[i] - seasonal[i]).collect();
+    Decomposition {
+        trend,
+        seasonal,
+        remainder,
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{lin_smooth, mean};
+
+/// Needs `1 <= n <= demand.len()`.
+pub fn moving_average(demand: &[f64], extra_periods: usize, n: usize) -> Vec<f64> {
+    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
+
+    for i in n..demand.len() {
+        forecast[i] = mean(&demand[i - n..i]);
+    }
+
+    let avg = mean(&demand[demand.len() - n..]);
+    forecast[demand.len()..].fill(avg);
+
+    forecast
+}
+
+/// Needs at least 1 period.
+pub fn exp_smooth(demand: &[f64], extra_periods: usize, alpha: f64) -> Vec<f64> {
+    let len = demand.len() + extra_periods;
+    let mut forecast = vec![f64::NAN; len];
+    if len < 2 {
+        return forecast;
+    }
+
+    forecast[1] = demand[0];
+
+    for i in 2..(demand.len() + 1).min(len) {
+        forecast[i] = lin_smooth(alpha, demand[i - 1], forecast[i - 1]);
+    }
+
+    for i in demand.len() + 1..len {
+        forecast[i] = forecast[i - 1];
+    }
+
+    forecast
+}
+
+/// Needs at least 2 periods.
+pub fn double_exp_smooth(demand: &[f64], extra_periods: usize, alpha: f64, beta: f64) -> Vec<f64> {
+    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
+
+    let mut a = demand[0];
+    let mut b = demand[1] - demand[0];
+
+    for i in 1..demand.len() {
+        forecast[i] = a + b;
+        let a_new = lin_smooth(alpha, demand[i], a + b);
+        b = lin_smooth(beta, a_new - a, b);
+        a = a_new;
+    }
+
+    for x in forecast[demand.len()..].iter_mut() {
+        *x = a + b;
+        a = *x;
+    }
+
+    forecast
+}
+
+/// Needs at least 2 periods.
+pub fn double_exp_smooth_damped(
+    demand: &[f64],
+    extra_periods: usize,
+    alpha: f64,
+    beta: f64,
+    phi: f64,
+) -> Vec<f64> {
+    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
+
+    let mut a = demand[0];
+    let mut b = demand[1] - demand[0];
+
+    for i in 1..demand.len() {
+        forecast[i] = a + phi * b;
+        let a_new = lin_smooth(alpha, demand[i], a + phi * b);
+        b = lin_smooth(beta, a_new - a, phi * b);
+        a = a_new;
+    }
+
+    for x in forecast[demand.len()..].iter_mut() {
+        *x = a + phi * b;
+        a = *x;
+        b *= phi;
+    }
+
+    forecast
+}
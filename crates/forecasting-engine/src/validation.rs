@@ -0,0 +1,147 @@
+use crate::model::ForecastModel;
+
+#[derive(Debug, Clone)]
+pub struct ValidationSettings {
+    pub enabled: bool,
+    /// Periods at the end of the series that are never used for fitting.
+    pub test_len: usize,
+    pub horizon: usize,
+    /// Refit from every origin in the test window instead of once at its start.
+    pub rolling: bool,
+}
+
+impl Default for ValidationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            test_len: 12,
+            horizon: 3,
+            rolling: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Errors {
+    pub count: usize,
+    pub mae: f64,
+    pub rmse: f64,
+    /// Periods with zero demand are left out.
+    pub mape: f64,
+    /// MAE over the in-sample MAE of the naive forecast.
+    pub mase: f64,
+    pub bias: f64,
+}
+
+impl Errors {
+    pub fn new(pairs: &[(f64, f64)], scale: f64) -> Self {
+        let pairs: Vec<_> = pairs
+            .iter()
+            .filter(|(forecast, actual)| forecast.is_finite() && actual.is_finite())
+            .collect();
+        let count = pairs.len();
+        if count == 0 {
+            return Self {
+                count,
+                mae: f64::NAN,
+                rmse: f64::NAN,
+                mape: f64::NAN,
+                mase: f64::NAN,
+                bias: f64::NAN,
+            };
+        }
+        let err: Vec<_> = pairs.iter().map(|(forecast, actual)| forecast - actual).collect();
+        let relative: Vec<_> = pairs
+            .iter()
+            .filter(|(_, actual)| *actual != 0.)
+            .map(|(forecast, actual)| ((forecast - actual) / actual).abs())
+            .collect();
+        let mae = err.iter().map(|x| x.abs()).sum::<f64>() / count as f64;
+        Self {
+            count,
+            mae,
+            rmse: (err.iter().map(|x| x * x).sum::<f64>() / count as f64).sqrt(),
+            mape: relative.iter().sum::<f64>() / relative.len() as f64,
+            mase: mae / scale,
+            bias: err.iter().sum::<f64>() / count as f64,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        format!(
+            "MAE {:.2} RMSE {:.2} MAPE {:.2} % MASE {:.2} Bias {:.2} (n {})",
+            self.mae,
+            self.rmse,
+            self.mape * 100.,
+            self.mase,
+            self.bias,
+            self.count
+        )
+    }
+}
+
+pub struct Validation {
+    pub overall: Errors,
+    /// Index 0 is the one step ahead forecast.
+    pub per_step: Vec<Errors>,
+    pub split: usize,
+    /// Forecast from the start of the test window, indexed like the demand.
+    pub test_forecast: Vec<[f64; 2]>,
+}
+
+pub fn validate(
+    model: &dyn ForecastModel,
+    demand: &[f64],
+    settings: &ValidationSettings,
+) -> Result<Validation, String> {
+    let horizon = settings.horizon.max(1);
+    if settings.test_len == 0 || settings.test_len >= demand.len() {
+        return Err(format!(
+            "test window of {} periods does not fit a series of {}",
+            settings.test_len,
+            demand.len()
+        ));
+    }
+    let split = demand.len() - settings.test_len;
+    if split < 2 {
+        return Err("at least 2 periods are needed for fitting".to_string());
+    }
+    if model.min_fit_len() > split {
+        return Err(format!(
+            "{} periods are needed for fitting, the test window leaves {}",
+            model.min_fit_len(),
+            split
+        ));
+    }
+
+    let origins = if settings.rolling {
+        split..demand.len()
+    } else {
+        split..split + 1
+    };
+    let mut per_step: Vec<Vec<(f64, f64)>> = vec![vec![]; horizon];
+    let mut test_forecast = vec![];
+    for origin in origins {
+        let forecast = model.forecast(&demand[..origin], horizon.max(settings.test_len))?;
+        for step in 0..horizon {
+            if origin + step < demand.len() {
+                per_step[step].push((forecast[origin + step], demand[origin + step]));
+            }
+        }
+        if origin == split {
+            test_forecast = (split..demand.len())
+                .map(|i| [i as f64, forecast[i]])
+                .collect();
+        }
+    }
+
+    // scaled by the fitting periods only, the test window stays unseen
+    let scale = crate::intermittent::naive_scale(&demand[..split]);
+    let all: Vec<_> = per_step.iter().flatten().copied().collect();
+    Ok(Validation {
+        overall: Errors::new(&all, scale),
+        per_step: per_step.iter().map(|pairs| Errors::new(pairs, scale)).collect(),
+        split,
+        test_forecast,
+    })
+}
@@ -0,0 +1,263 @@
+use crate::{DemandSource, ForecastMethod};
+use forecasting_engine::intervals::Intervals;
+
+#[derive(serde::Serialize)]
+pub struct Metrics {
+    pub method: String,
+    pub parameters: String,
+    pub bias: f64,
+    pub bias_rel: f64,
+    pub mape: f64,
+    pub mae: f64,
+    pub mae_rel: f64,
+    pub rmse: f64,
+    pub rmse_rel: f64,
+    pub mase: f64,
+    /// Out of sample errors, present when validation is on.
+    pub test_mae: Option<f64>,
+    pub test_rmse: Option<f64>,
+    pub test_mape: Option<f64>,
+    pub test_mase: Option<f64>,
+}
+
+impl Metrics {
+    pub fn new(method: &ForecastMethod) -> Self {
+        let test = match method.validation() {
+            Some(Ok(x)) => Some(&x.overall),
+            _ => None,
+        };
+        Self {
+            method: method.name.to_string(),
+            parameters: method.model.describe(),
+            bias: method.result.bias_abs,
+            bias_rel: method.result.bias_rel,
+            mape: method.result.mape,
+            mae: method.result.mae,
+            mae_rel: method.result.mae_rel,
+            rmse: method.result.rmse,
+            rmse_rel: method.result.rmse_rel,
+            mase: method.result.mase,
+            test_mae: test.map(|x| x.mae),
+            test_rmse: test.map(|x| x.rmse),
+            test_mape: test.map(|x| x.mape),
+            test_mase: test.map(|x| x.mase),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct Band {
+    pub level: f64,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Series {
+    pub method: String,
+    pub forecast: Vec<f64>,
+    /// Forecast minus demand, indexed like the demand.
+    pub err: Vec<f64>,
+    /// Bands for the periods after the demand.
+    pub intervals: Vec<Band>,
+}
+
+impl Series {
+    pub fn new(method: &ForecastMethod, demand: &[f64], intervals: Option<&Intervals>) -> Self {
+        Self {
+            method: method.name.to_string(),
+            forecast: method.result.forecast.clone(),
+            err: errors(&method.result.forecast, demand),
+            intervals: intervals
+                .iter()
+                .flat_map(|x| x.bands.iter())
+                .map(|x| Band {
+                    level: x.level,
+                    lower: x.lower.clone(),
+                    upper: x.upper.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct Export {
+    pub source: String,
+    pub dates: Vec<String>,
+    pub demand: Vec<f64>,
+    pub series: Vec<Series>,
+    pub metrics: Vec<Metrics>,
+}
+
+/// Bands of the i-th method, the slice is empty when intervals are off.
+fn bands(intervals: &[Option<Intervals>], i: usize) -> Option<&Intervals> {
+    intervals.get(i).and_then(|x| x.as_ref())
+}
+
+impl Export {
+    pub fn new(source: &DemandSource, forecasts: &[ForecastMethod], intervals: &[Option<Intervals>]) -> Self {
+        Self {
+            source: source.name.clone(),
+            dates: source.dates.clone(),
+            demand: source.demand.clone(),
+            series: forecasts
+                .iter()
+                .enumerate()
+                .map(|(i, x)| Series::new(x, &source.demand, bands(intervals, i)))
+                .collect(),
+            metrics: forecasts.iter().map(Metrics::new).collect(),
+        }
+    }
+}
+
+fn errors(forecast: &[f64], demand: &[f64]) -> Vec<f64> {
+    forecast.iter().zip(demand.iter()).map(|(f, d)| f - d).collect()
+}
+
+/// NaN, e.g. before a moving average has a full window, becomes an empty cell.
+fn cell(value: f64) -> String {
+    if value.is_finite() { value.to_string() } else { String::default() }
+}
+
+/// One row per period: demand, then forecast and error of every method, then its interval bands.
+pub fn series_csv(
+    source: &DemandSource,
+    forecasts: &[ForecastMethod],
+    intervals: &[Option<Intervals>],
+) -> Result<String, String> {
+    let periods = forecasts
+        .iter()
+        .map(|x| x.result.forecast.len())
+        .max()
+        .unwrap_or(0)
+        .max(source.demand.len());
+    let series: Vec<Series> = forecasts
+        .iter()
+        .enumerate()
+        .map(|(i, x)| Series::new(x, &source.demand, bands(intervals, i)))
+        .collect();
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let mut headers = vec!["period".to_string(), "date".to_string(), "demand".to_string()];
+    for s in series.iter() {
+        headers.push(format!("{} forecast", s.method));
+        headers.push(format!("{} err", s.method));
+        for band in s.intervals.iter() {
+            headers.push(format!("{} lower {:.0} %", s.method, band.level * 100.));
+            headers.push(format!("{} upper {:.0} %", s.method, band.level * 100.));
+        }
+    }
+    writer.write_record(&headers).map_err(|e| e.to_string())?;
+
+    for i in 0..periods {
+        let mut row = vec![
+            i.to_string(),
+            source.dates.get(i).cloned().unwrap_or_default(),
+            source.demand.get(i).map(|&x| cell(x)).unwrap_or_default(),
+        ];
+        for s in series.iter() {
+            row.push(s.forecast.get(i).map(|&x| cell(x)).unwrap_or_default());
+            row.push(s.err.get(i).map(|&x| cell(x)).unwrap_or_default());
+            let step = i.checked_sub(source.demand.len());
+            for band in s.intervals.iter() {
+                row.push(step.and_then(|x| band.lower.get(x)).map(|&x| cell(x)).unwrap_or_default());
+                row.push(step.and_then(|x| band.upper.get(x)).map(|&x| cell(x)).unwrap_or_default());
+            }
+        }
+        writer.write_record(&row).map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+pub fn metrics_csv(forecasts: &[ForecastMethod]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for metrics in forecasts.iter().map(Metrics::new) {
+        writer.serialize(metrics).map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// `.json` files get the series and the metrics together, anything else is csv.
+/// `intervals` holds the bands per method, computed for the export only.
+pub fn export(
+    path: &str,
+    metrics_only: bool,
+    source: &DemandSource,
+    forecasts: &[ForecastMethod],
+    intervals: &[Option<Intervals>],
+) -> Result<(), String> {
+    let text = if path.to_lowercase().ends_with(".json") {
+        if metrics_only {
+            serde_json::to_string_pretty(&forecasts.iter().map(Metrics::new).collect::<Vec<_>>())
+        } else {
+            serde_json::to_string_pretty(&Export::new(source, forecasts, intervals))
+        }
+        .map_err(|e| e.to_string())?
+    } else if metrics_only {
+        metrics_csv(forecasts)?
+    } else {
+        series_csv(source, forecasts, intervals)?
+    };
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Markdown summary of the parameters and metrics of every method, best first by `metric`.
+pub fn report(source: &DemandSource, forecasts: &[ForecastMethod], metric: forecasting_engine::optimise::Metric) -> String {
+    // out of sample errors are what the choice should rest on, methods that failed validation go last
+    let validated = forecasts.iter().any(|m| matches!(m.validation(), Some(Ok(_))));
+    let score = |m: &ForecastMethod| match m.validation() {
+        _ if !validated => metric.score(&m.result),
+        Some(Ok(x)) => metric.score_errors(&x.overall),
+        _ => f64::INFINITY,
+    };
+    let mut order: Vec<usize> = (0..forecasts.len()).collect();
+    order.sort_by(|&a, &b| score(&forecasts[a]).total_cmp(&score(&forecasts[b])));
+
+    let mut text = format!("## Forecast {}\n\n", source.name);
+    text += &format!("{} periods", source.demand.len());
+    if let (Some(first), Some(last)) = (source.dates.first(), source.dates.last()) {
+        text += &format!(" from {} to {}", first, last);
+    }
+    text += "\n";
+    if let Some(classification) = forecasting_engine::intermittent::classify(&source.demand) {
+        text += &format!("Demand {}\n", classification.format());
+    }
+    if let Some(period) = forecasting_engine::seasonality::detect(&source.demand) {
+        text += &format!("Seasonal period {} (ACF {:.2})\n", period.len, period.acf);
+    }
+    if let Some(&best) = order.first() {
+        text += &format!(
+            "\nBest method by {} {}: **{}** ({})\n",
+            if validated { "out of sample" } else { "in sample" },
+            metric.name(),
+            forecasts[best].name,
+            forecasts[best].model.describe()
+        );
+    }
+
+    text += "\n| method | parameters | bias | MAPE | MAE | RMSE | MASE | out of sample |\n";
+    text += "|---|---|---|---|---|---|---|---|\n";
+    for &i in order.iter() {
+        let m = &forecasts[i];
+        let test = match m.validation() {
+            Some(Ok(x)) => x.overall.format(),
+            Some(Err(err)) => err.clone(),
+            None => String::default(),
+        };
+        text += &format!(
+            "| {} | {} | {:.2} | {:.2} % | {:.2} | {:.2} | {:.2} | {} |\n",
+            m.name,
+            m.model.describe(),
+            m.result.bias_abs,
+            m.result.mape * 100.,
+            m.result.mae,
+            m.result.rmse,
+            m.result.mase,
+            test
+        );
+    }
+    text
+}
@@ -0,0 +1,384 @@
+use crate::DemandSource;
+
+/// Raw cells of an imported file, the first row of a csv becomes the headers.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn column(&self, index: usize) -> impl Iterator<Item = &str> {
+        self.rows
+            .iter()
+            .map(move |row| row.get(index).map(|x| x.as_str()).unwrap_or_default())
+    }
+}
+
+/// An import in progress, the user picks the columns before the series are added.
+pub struct Import {
+    pub name: String,
+    pub table: Table,
+    pub delimiter: Option<u8>,
+    pub decimal_comma: bool,
+    pub date_column: Option<usize>,
+    pub value_columns: Vec<bool>,
+}
+
+impl Import {
+    pub fn from_bytes(name: &str, bytes: &[u8]) -> Result<Self, String> {
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_start_matches('\u{feff}');
+        let (table, delimiter) = if name.to_lowercase().ends_with(".json") {
+            (parse_json(text)?, None)
+        } else {
+            let delimiter = detect_delimiter(text);
+            (parse_csv(text, delimiter)?, Some(delimiter))
+        };
+        if table.rows.is_empty() {
+            return Err(format!("{} contains no rows", name));
+        }
+
+        let decimal_comma = delimiter != Some(b',') && detect_decimal_comma(&table);
+        // years or `20240301` parse as dates too, a column of only numbers is taken as values
+        let date_column = (0..table.headers.len()).find(|&i| {
+            table.column(i).all(|x| parse_date(x).is_some()) && !table.column(i).all(|x| x.parse::<f64>().is_ok())
+        });
+        let value_columns = (0..table.headers.len())
+            .map(|i| Some(i) != date_column && table.column(i).all(|x| parse_number(x, decimal_comma).is_some()))
+            .collect();
+
+        let name = std::path::Path::new(name)
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.to_string());
+        Ok(Self {
+            name,
+            table,
+            delimiter,
+            decimal_comma,
+            date_column,
+            value_columns,
+        })
+    }
+
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::from_bytes(path, &bytes)
+    }
+
+    /// One source per selected column, rows are ordered by the date column if there is one.
+    pub fn sources(&self) -> Result<Vec<DemandSource>, String> {
+        let mut rows: Vec<&Vec<String>> = self.table.rows.iter().collect();
+        let mut dates = vec![];
+        if let Some(date_column) = self.date_column {
+            let mut parsed = vec![];
+            for (i, row) in rows.iter().enumerate() {
+                let cell = row.get(date_column).map(|x| x.as_str()).unwrap_or_default();
+                let date = parse_date(cell)
+                    .ok_or_else(|| format!("row {}: {:?} is not a date", i + 1, cell))?;
+                parsed.push((date, *row));
+            }
+            parsed.sort_by_key(|x| x.0);
+            dates = parsed.iter().map(|x| x.0.to_string()).collect();
+            rows = parsed.into_iter().map(|x| x.1).collect();
+        }
+
+        let mut sources = vec![];
+        for (column, _) in self.value_columns.iter().enumerate().filter(|x| *x.1) {
+            let mut demand = vec![];
+            for (i, row) in rows.iter().enumerate() {
+                let cell = row.get(column).map(|x| x.as_str()).unwrap_or_default();
+                demand.push(
+                    parse_number(cell, self.decimal_comma)
+                        .ok_or_else(|| format!("{} row {}: {:?} is not a number", self.table.headers[column], i + 1, cell))?,
+                );
+            }
+            let mut source = DemandSource::new(format!("{} {}", self.name, self.table.headers[column]), demand);
+            source.dates = dates.clone();
+            sources.push(source);
+        }
+        if sources.is_empty() {
+            return Err("no value column selected".to_string());
+        }
+        Ok(sources)
+    }
+}
+
+/// The candidate that splits the header and the first rows into the same, largest number of fields.
+pub fn detect_delimiter(text: &str) -> u8 {
+    let lines: Vec<_> = text.lines().filter(|x| !x.trim().is_empty()).take(10).collect();
+    let mut best = (b',', 0);
+    for delimiter in [b',', b';', b'\t', b'|'] {
+        let counts: Vec<_> = lines
+            .iter()
+            .map(|line| line.bytes().filter(|&x| x == delimiter).count())
+            .collect();
+        let Some(&first) = counts.first() else {
+            continue;
+        };
+        if first > best.1 && counts.iter().all(|&x| x == first) {
+            best = (delimiter, first);
+        }
+    }
+    best.0
+}
+
+pub fn parse_csv(text: &str, delimiter: u8) -> Result<Table, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|x| x.trim().to_string())
+        .collect();
+    let mut rows = vec![];
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        if record.iter().all(|x| x.trim().is_empty()) {
+            continue;
+        }
+        rows.push(record.iter().map(|x| x.trim().to_string()).collect());
+    }
+    Ok(Table { headers, rows })
+}
+
+/// Accepts a list of records `[{"date": .., "sales": ..}]` or named series `{"sales": [1, 2, 3]}`.
+pub fn parse_json(text: &str) -> Result<Table, String> {
+    let value = serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())?;
+    match value {
+        serde_json::Value::Array(records) => {
+            let mut headers: Vec<String> = vec![];
+            for record in records.iter() {
+                let serde_json::Value::Object(record) = record else {
+                    return Err("expected a list of objects".to_string());
+                };
+                for key in record.keys() {
+                    if !headers.contains(key) {
+                        headers.push(key.clone());
+                    }
+                }
+            }
+            let rows = records
+                .iter()
+                .map(|record| headers.iter().map(|key| cell(record.get(key))).collect())
+                .collect();
+            Ok(Table { headers, rows })
+        }
+        serde_json::Value::Object(series) => {
+            let headers: Vec<String> = series.keys().cloned().collect();
+            let mut columns = vec![];
+            for (key, values) in series.iter() {
+                let serde_json::Value::Array(values) = values else {
+                    return Err(format!("{} is not a list", key));
+                };
+                columns.push(values);
+            }
+            let len = columns.iter().map(|x| x.len()).max().unwrap_or(0);
+            let rows = (0..len)
+                .map(|i| columns.iter().map(|column| cell(column.get(i))).collect())
+                .collect();
+            Ok(Table { headers, rows })
+        }
+        _ => Err("expected a list of objects or an object of lists".to_string()),
+    }
+}
+
+fn cell(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(x)) => x.clone(),
+        Some(serde_json::Value::Null) | None => String::default(),
+        Some(x) => x.to_string(),
+    }
+}
+
+/// `1.234,5` style numbers win if they are more common than `1,234.5` ones.
+pub fn detect_decimal_comma(table: &Table) -> bool {
+    let mut comma = 0;
+    let mut point = 0;
+    for cell in table.rows.iter().flatten() {
+        if parse_date(cell).is_some() {
+            continue;
+        }
+        match (cell.rfind(','), cell.rfind('.')) {
+            (Some(c), Some(p)) if c > p => comma += 1,
+            (Some(_), Some(_)) => point += 1,
+            (Some(c), None) if cell.len() - c - 1 != 3 => comma += 1,
+            (None, Some(_)) => point += 1,
+            _ => {}
+        }
+    }
+    comma > point
+}
+
+pub fn parse_number(text: &str, decimal_comma: bool) -> Option<f64> {
+    let mut text: String = text
+        .chars()
+        .filter(|x| !x.is_whitespace() && *x != '\'' && *x != '_')
+        .collect();
+    if decimal_comma {
+        text = text.replace('.', "").replace(',', ".");
+    } else {
+        text = text.replace(',', "");
+    }
+    text.parse::<f64>().ok().filter(|x| x.is_finite())
+}
+
+const DATE_FORMATS: [&str; 6] = ["%Y-%m-%d", "%d.%m.%Y", "%m/%d/%Y", "%Y/%m/%d", "%d-%m-%Y", "%Y%m%d"];
+
+/// Months (`2024-03`, `03/2024`) and years map to their first day.
+/// Years need four digits from 1000 on, otherwise decimals like `1.5` would be March of year 5.
+pub fn parse_date(text: &str) -> Option<chrono::NaiveDate> {
+    use chrono::Datelike;
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let day = text.split(['T', ' ']).next().unwrap_or(text);
+    let four_digits = |date: &chrono::NaiveDate| (1000..=9999).contains(&date.year());
+    for format in DATE_FORMATS {
+        if let Some(date) = chrono::NaiveDate::parse_from_str(day, format).ok().filter(four_digits) {
+            return Some(date);
+        }
+    }
+    for format in ["%Y-%m-%d", "%m/%Y-%d", "%m.%Y-%d", "%Y/%m-%d"] {
+        if let Some(date) = chrono::NaiveDate::parse_from_str(&format!("{}-01", text), format)
+            .ok()
+            .filter(four_digits)
+        {
+            return Some(date);
+        }
+    }
+    if text.len() == 4 && text.bytes().all(|x| x.is_ascii_digit()) {
+        let year = text.parse::<i32>().ok()?;
+        return chrono::NaiveDate::from_ymd_opt(year, 1, 1).filter(four_digits);
+    }
+    None
+}
+
+/// Returns the sources of a finished import.
+pub fn show_import(ui: &mut egui::Ui, import: &mut Import) -> Option<Result<Vec<DemandSource>, String>> {
+    let mut result = None;
+    ui.heading(format!("Import {}", import.name));
+    ui.horizontal(|ui| {
+        ui.label("name ");
+        ui.text_edit_singleline(&mut import.name);
+    });
+    ui.horizontal(|ui| {
+        if let Some(delimiter) = import.delimiter {
+            ui.label(format!("delimiter {:?}", delimiter as char));
+        }
+        ui.checkbox(&mut import.decimal_comma, "decimal comma");
+    });
+
+    egui::ComboBox::from_label("date column")
+        .selected_text(
+            import
+                .date_column
+                .map(|x| import.table.headers[x].clone())
+                .unwrap_or_else(|| "none".to_string()),
+        )
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut import.date_column, None, "none");
+            for (i, header) in import.table.headers.iter().enumerate() {
+                ui.selectable_value(&mut import.date_column, Some(i), header);
+            }
+        });
+
+    ui.label("value columns");
+    ui.horizontal_wrapped(|ui| {
+        for (i, header) in import.table.headers.iter().enumerate() {
+            ui.checkbox(&mut import.value_columns[i], header);
+        }
+    });
+
+    egui::ScrollArea::both().max_height(200.0).show(ui, |ui| {
+        egui::Grid::new("import_preview").striped(true).show(ui, |ui| {
+            for header in import.table.headers.iter() {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for row in import.table.rows.iter().take(20) {
+                for cell in row.iter() {
+                    ui.label(cell);
+                }
+                ui.end_row();
+            }
+        });
+    });
+    ui.label(format!("{} rows", import.table.rows.len()));
+
+    ui.horizontal(|ui| {
+        if ui.button("Add").clicked() {
+            result = Some(import.sources());
+        }
+        if ui.button("Cancel").clicked() {
+            result = Some(Ok(vec![]));
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> Option<chrono::NaiveDate> {
+        chrono::NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn delimiter() {
+        assert_eq!(detect_delimiter("date,sales\n2024-01-01,3\n2024-01-02,4"), b',');
+        assert_eq!(detect_delimiter("date;sales;returns\n01.01.2024;3,5;1\n02.01.2024;4,25;0"), b';');
+        assert_eq!(detect_delimiter("date\tsales\n2024-01-01\t1,200\n2024-01-02\t900"), b'\t');
+        assert_eq!(detect_delimiter("sales\n3\n4"), b',');
+    }
+
+    #[test]
+    fn decimal_comma() {
+        let table = parse_csv("date;sales\n01.01.2024;3,5\n02.01.2024;1.204,25\n03.01.2024;7", b';').unwrap();
+        assert!(detect_decimal_comma(&table));
+        let table = parse_csv("date,sales\n2024-01-01,3.5\n2024-01-02,\"1,204.25\"", b',').unwrap();
+        assert!(!detect_decimal_comma(&table));
+        // a single comma before three digits is a thousands separator
+        let table = parse_csv("sales\n\"1,200\"\n\"3,400\"", b',').unwrap();
+        assert!(!detect_decimal_comma(&table));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("2024-03-05"), date(2024, 3, 5));
+        assert_eq!(parse_date("05.03.2024"), date(2024, 3, 5));
+        assert_eq!(parse_date("2024-03-05T10:00:00Z"), date(2024, 3, 5));
+        assert_eq!(parse_date("2024-03"), date(2024, 3, 1));
+        assert_eq!(parse_date("03/2024"), date(2024, 3, 1));
+        assert_eq!(parse_date("03.2024"), date(2024, 3, 1));
+        assert_eq!(parse_date("2024"), date(2024, 1, 1));
+        for text in ["1.5", "10.2", "12.5", "3.7", "0999", "999", "12345", "1-2-3", "sales"] {
+            assert_eq!(parse_date(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn date_column() {
+        let import = Import::from_bytes("sales.csv", b"sales;date\n3,5;01.01.2024\n4;02.01.2024").unwrap();
+        assert_eq!(import.delimiter, Some(b';'));
+        assert!(import.decimal_comma);
+        assert_eq!(import.date_column, Some(1));
+        assert_eq!(import.value_columns, vec![true, false]);
+
+        // decimals are no months
+        let import = Import::from_bytes("sales.csv", b"a,b\n1.5,10.2\n12.5,3.7").unwrap();
+        assert_eq!(import.date_column, None);
+        assert_eq!(import.value_columns, vec![true, true]);
+
+        // neither are numbers that happen to look like years
+        let import = Import::from_bytes("sales.csv", b"units,sales\n2019,3\n2020,4").unwrap();
+        assert_eq!(import.date_column, None);
+        assert_eq!(import.value_columns, vec![true, true]);
+    }
+}
@@ -1,9 +1,22 @@
-use egui::Ui;
+use egui::{Ui, Widget};
 use egui_plot::Legend;
+use forecasting_engine::{
+    ForecastModel, ForecastResult, intermittent, intervals, model, models, optimise, seasonality, validation,
+};
+
+mod export;
+mod import;
+mod parameters;
 
 fn main() {
     common::app::run("forecasting", |cc| {
-        let mut parameters = ForecastParameters::default();
+        let settings: Settings = if let Some(storage) = cc.storage {
+            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
+        } else {
+            Default::default()
+        };
+
+        let mut options = Options::default();
 
         let mut sources = vec![];
         let mut source_id = 0;
@@ -247,25 +260,148 @@ fn main() {
             ],
         ));
 
-        let mut forecasts = methods(&sources[0].demand, &parameters);
+        sources.push(DemandSource::new(
+            "intermittent test",
+            vec![
+                0., 3., 0., 0., 5., 0., 0., 0., 2., 0., 4., 0., 0., 0., 0., 6., 0., 1., 0., 0., 3.,
+                0., 0., 7., 0., 0., 0., 2., 0., 0.,
+            ],
+        ));
+
+        let builtin_sources = sources.len();
+        sources.extend(settings.sources);
+
+        let mut forecasts = methods(&sources[0].demand, options.extra_periods);
+
+        let mut band_method = 0;
+        let mut period = seasonality::detect(&sources[0].demand);
+        if let Some(period) = period {
+            for (i, m) in forecasts.iter_mut().enumerate() {
+                if model::set_season_len(m.model.as_mut(), period.len) {
+                    m.update(&cc.egui_ctx, &sources[0].demand, &options, i == band_method);
+                }
+            }
+        }
+        let mut show_decomposition = false;
+        let mut decomposition_period = period.map(|x| x.len).unwrap_or(12);
 
         let mut show_err = false;
+        let mut scaled_err = false;
+        let mut metric = optimise::Metric::Mae;
+        let mut fits: Vec<Option<poll_promise::Promise<optimise::Fit>>> = forecasts.iter().map(|_| None).collect();
+        let mut import_path = String::default();
+        let mut import: Option<import::Import> = None;
+        let mut import_error: Option<String> = None;
+        let mut export_file = "forecast.csv".to_string();
+        let mut export_status: Option<Result<String, String>> = None;
+        // bands of every method are only computed for an export of the series
+        let mut export_job: Option<(String, poll_promise::Promise<Vec<Option<intervals::Intervals>>>)> = None;
+
+        return Box::new(move |mut ctx| {
+            let mut changed = false;
+            let mut source_changed = false;
+            let mut save = false;
+            let mut apply = None;
+
+            let dropped = ctx.ui.ctx().input(|i| i.raw.dropped_files.clone());
+            for file in dropped {
+                let result = match (&file.bytes, &file.path) {
+                    (Some(bytes), _) => import::Import::from_bytes(&file.name, bytes),
+                    (None, Some(path)) => import::Import::from_path(&path.to_string_lossy()),
+                    (None, None) => Err(format!("{} could not be read", file.name)),
+                };
+                match result {
+                    Ok(x) => import = Some(x),
+                    Err(err) => import_error = Some(err),
+                }
+            }
+
+            if let Some(current) = &mut import {
+                let modal = egui::Modal::new(egui::Id::new("import")).show(ctx.ui.ctx(), |ui| {
+                    ui.set_width(800.0);
+                    import::show_import(ui, current)
+                });
+                match modal.inner {
+                    Some(Ok(new_sources)) => {
+                        if !new_sources.is_empty() {
+                            source_id = sources.len();
+                            sources.extend(new_sources);
+                            changed = true;
+                            source_changed = true;
+                            save = true;
+                        }
+                        import = None;
+                    }
+                    Some(Err(err)) => import_error = Some(err),
+                    None if modal.should_close() => import = None,
+                    None => {}
+                }
+            }
 
-        return Box::new(move |ctx| {
-            let ui = ctx.ui;
+            let ui = &mut *ctx.ui;
 
             ui.horizontal(|ui| {
                 ui.vertical(|ui| {
-                    let mut changed = false;
-                    changed |= slider_u(ui, &mut parameters.extra_periods, "extra periods");
-                    changed |= slider_u(ui, &mut parameters.season_len, "season length");
-                    changed |= slider_u(ui, &mut parameters.n, "n");
-                    changed |= slider_f(ui, &mut parameters.alpha, "alpha");
-                    changed |= slider_f(ui, &mut parameters.beta, "beta");
-                    changed |= slider_f(ui, &mut parameters.phi, "phi");
-                    changed |= slider_f(ui, &mut parameters.gamma, "gamma");
+                    changed |= slider_u(ui, &mut options.extra_periods, "extra periods");
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut options.validation.enabled, "validate").changed();
+                        if options.validation.enabled {
+                            changed |= ui.checkbox(&mut options.validation.rolling, "rolling origin").changed();
+                        }
+                    });
+                    if options.validation.enabled {
+                        let max_test = sources[source_id].demand.len().saturating_sub(2).max(1);
+                        changed |= ui
+                            .add(egui::Slider::new(&mut options.validation.test_len, 1..=max_test).text("test periods"))
+                            .changed();
+                        changed |= ui
+                            .add(egui::Slider::new(&mut options.validation.horizon, 1..=12).text("horizon"))
+                            .changed();
+                    }
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut options.intervals.enabled, "intervals").changed();
+                        if options.intervals.enabled {
+                            for method in [intervals::IntervalMethod::Normal, intervals::IntervalMethod::Bootstrap] {
+                                changed |= ui
+                                    .radio_value(&mut options.intervals.method, method, method.name())
+                                    .changed();
+                            }
+                        }
+                    });
+                    if options.intervals.enabled {
+                        let previous = band_method;
+                        egui::ComboBox::from_label("bands for")
+                            .selected_text(forecasts[band_method].name)
+                            .show_ui(ui, |ui| {
+                                for (i, m) in forecasts.iter().enumerate() {
+                                    ui.selectable_value(&mut band_method, i, m.name);
+                                }
+                            });
+                        if band_method != previous {
+                            forecasts[previous].intervals = None;
+                            forecasts[band_method].update_intervals(ui.ctx(), &sources[source_id].demand, &options);
+                        }
+                        if forecasts[band_method].intervals.as_ref().is_some_and(|x| x.ready().is_none()) {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.weak("computing intervals");
+                            });
+                        }
+                    }
+                    for (i, m) in forecasts.iter_mut().enumerate() {
+                        egui::CollapsingHeader::new(m.name).show(ui, |ui| {
+                            if parameters::show(m.model.as_mut(), ui) {
+                                m.update(ui.ctx(), &sources[source_id].demand, &options, i == band_method);
+                            }
+                        });
+                    }
 
-                    ui.checkbox(&mut show_err, "show err");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut show_err, "show err");
+                        if show_err {
+                            ui.checkbox(&mut scaled_err, "scaled");
+                        }
+                    });
 
                     egui::ComboBox::from_label("demand source")
                         .selected_text(sources[source_id].name.clone())
@@ -276,36 +412,240 @@ fn main() {
                                 if i_temp != source_id {
                                     source_id = i_temp;
                                     changed = true;
+                                    source_changed = true;
                                 }
                             }
                         });
 
-                    if changed {
-                        for m in forecasts.iter_mut() {
-                            m.update(&sources[source_id].demand, &parameters);
+                    if let Some(classification) = intermittent::classify(&sources[source_id].demand) {
+                        ui.label(classification.format());
+                    }
+                    ui.horizontal(|ui| {
+                        match period {
+                            Some(period) => ui.label(format!("period {} (ACF {:.2})", period.len, period.acf)),
+                            None => ui.label("no seasonality detected"),
+                        };
+                        ui.checkbox(&mut show_decomposition, "decomposition");
+                    });
+                    if show_decomposition {
+                        let max_period = (sources[source_id].demand.len() / 2).max(1);
+                        ui.add(egui::Slider::new(&mut decomposition_period, 1..=max_period).text("decomposition period"));
+                    }
+
+                    ui.horizontal(|ui| {
+                        egui::TextEdit::singleline(&mut import_path)
+                            .hint_text("csv or json file, or drop one")
+                            .ui(ui);
+                        if ui.button("import").clicked() {
+                            match import::Import::from_path(&import_path) {
+                                Ok(x) => import = Some(x),
+                                Err(err) => import_error = Some(err),
+                            }
+                        }
+                        if source_id >= builtin_sources && ui.button("remove").clicked() {
+                            sources.remove(source_id);
+                            source_id = 0;
+                            changed = true;
+                            source_changed = true;
+                            save = true;
                         }
+                    });
+                    if let Some(err) = import_error.clone() {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(ui.visuals().error_fg_color, err);
+                            if ui.button("x").clicked() {
+                                import_error = None;
+                            }
+                        });
                     }
                 });
                 ui.vertical(|ui| {
-                    for m in forecasts.iter() {
-                        ui.label(format!("{}", m.name));
+                    egui::ComboBox::from_label("optimise for")
+                        .selected_text(metric.name())
+                        .show_ui(ui, |ui| {
+                            for x in optimise::Metric::ALL {
+                                ui.selectable_value(&mut metric, x, x.name());
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        egui::TextEdit::singleline(&mut export_file)
+                            .hint_text("export file, csv or json")
+                            .ui(ui);
+                        if ui.add_enabled(export_job.is_none(), egui::Button::new("export series")).clicked() {
+                            if options.intervals.enabled {
+                                let jobs: Vec<_> = forecasts
+                                    .iter()
+                                    .map(|m| (m.error.is_none(), m.model.clone(), m.result.forecast.clone()))
+                                    .collect();
+                                let demand = sources[source_id].demand.clone();
+                                let extra_periods = options.extra_periods;
+                                let settings = options.intervals.clone();
+                                export_job = Some((
+                                    export_file.clone(),
+                                    spawn(ui.ctx(), move || {
+                                        jobs.iter()
+                                            .map(|(ok, model, forecast)| {
+                                                ok.then(|| {
+                                                    intervals::intervals(
+                                                        model.as_ref(),
+                                                        &demand,
+                                                        forecast,
+                                                        extra_periods,
+                                                        &settings,
+                                                    )
+                                                })
+                                            })
+                                            .collect()
+                                    }),
+                                ));
+                            } else {
+                                export_status = Some(
+                                    export::export(&export_file, false, &sources[source_id], &forecasts, &[])
+                                        .map(|_| format!("exported to {}", export_file)),
+                                );
+                            }
+                        }
+                        if ui.button("export metrics").clicked() {
+                            export_status = Some(
+                                export::export(&export_file, true, &sources[source_id], &forecasts, &[])
+                                    .map(|_| format!("exported to {}", export_file)),
+                            );
+                        }
+                        if ui.button("copy report").clicked() {
+                            ui.ctx().copy_text(export::report(&sources[source_id], &forecasts, metric));
+                            export_status = Some(Ok("report copied".to_string()));
+                        }
+                    });
+                    if let Some((path, job)) = &export_job {
+                        match job.ready() {
+                            Some(bands) => {
+                                export_status = Some(
+                                    export::export(path, false, &sources[source_id], &forecasts, bands)
+                                        .map(|_| format!("exported to {}", path)),
+                                );
+                                export_job = None;
+                            }
+                            None => {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.weak("computing intervals for the export");
+                                });
+                            }
+                        }
+                    }
+                    match &export_status {
+                        Some(Ok(status)) => {
+                            ui.weak(status);
+                        }
+                        Some(Err(err)) => {
+                            ui.colored_label(ui.visuals().error_fg_color, err);
+                        }
+                        None => {}
+                    }
+                    for (i, m) in forecasts.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}", m.name));
+                            let running = fits[i].as_ref().is_some_and(|x| x.ready().is_none());
+                            if ui.add_enabled(!running, egui::Button::new("optimise").small()).clicked() {
+                                let model = m.model.clone();
+                                let demand = sources[source_id].demand.clone();
+                                let extra_periods = options.extra_periods;
+                                fits[i] = Some(spawn(ui.ctx(), move || {
+                                    optimise::optimise(model.as_ref(), &demand, extra_periods, metric)
+                                }));
+                            }
+                            match fits[i].as_ref().map(|x| x.ready()) {
+                                Some(Some(fit)) => {
+                                    ui.weak(fit.describe());
+                                    if ui.small_button("apply").clicked() {
+                                        apply = Some((i, fit.model.clone()));
+                                    }
+                                }
+                                Some(None) => {
+                                    ui.spinner();
+                                }
+                                None => {}
+                            }
+                        });
                         ui.label(format!(
-                            "Bias {:.0} {:.2} % MAPE {:.2} % MEA {:.0} {:.2} % RSME {:.0} {:.2} %",
+                            "Bias {:.0} {:.2} % MAPE {:.2} % MEA {:.0} {:.2} % RSME {:.0} {:.2} % MASE {:.2}",
                             m.result.bias_abs,
                             m.result.bias_rel * 100.,
                             m.result.mape * 100.,
                             m.result.mae,
                             m.result.mae_rel * 100.,
                             m.result.rmse,
-                            m.result.rmse_rel * 100.
+                            m.result.rmse_rel * 100.,
+                            m.result.mase
                         ));
+                        if let Some(err) = &m.error {
+                            ui.colored_label(ui.visuals().error_fg_color, err);
+                        }
+                        match m.validation.as_ref().map(|x| x.ready()) {
+                            Some(Some(Ok(result))) => {
+                                egui::CollapsingHeader::new(format!("out of sample {}", result.overall.format()))
+                                    .id_salt(("validation", i))
+                                    .show(ui, |ui| {
+                                        for (step, errors) in result.per_step.iter().enumerate() {
+                                            ui.label(format!("t+{} {}", step + 1, errors.format()));
+                                        }
+                                    });
+                            }
+                            Some(Some(Err(err))) => {
+                                ui.colored_label(ui.visuals().error_fg_color, err);
+                            }
+                            Some(None) => {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.weak("validating");
+                                });
+                            }
+                            None => {}
+                        }
                     }
                 });
             });
 
+            if show_decomposition {
+                let decomposition = seasonality::decompose(&sources[source_id].demand, decomposition_period);
+                for (name, values) in [
+                    ("trend", &decomposition.trend),
+                    ("seasonal", &decomposition.seasonal),
+                    ("remainder", &decomposition.remainder),
+                ] {
+                    egui_plot::Plot::new(("decomposition", name))
+                        .height(100.0)
+                        .link_axis("decomposition", [true, false])
+                        .legend(Legend::default())
+                        .show(ui, |plot_ui| {
+                            plot_ui.line(egui_plot::Line::new(name, egui_plot::PlotPoints::from_ys_f64(values)));
+                        });
+                }
+            }
+
             egui_plot::Plot::new("plot")
                 .legend(Legend::default().follow_insertion_order(true))
                 .show(ui, |plot_ui| {
+                    if options.intervals.enabled && !show_err {
+                        let method = &forecasts[band_method];
+                        if let Some(intervals) = method.intervals.as_ref().and_then(|x| x.ready()) {
+                            for band in intervals.bands.iter().rev() {
+                                let upper = band.upper.iter().enumerate();
+                                let lower = band.lower.iter().enumerate().rev();
+                                let points: Vec<[f64; 2]> = upper
+                                    .chain(lower)
+                                    .map(|(i, &y)| [(intervals.start + i) as f64, y])
+                                    .collect();
+                                plot_ui.polygon(
+                                    egui_plot::Polygon::new(
+                                        format!("{} {:.0} %", method.name, band.level * 100.),
+                                        points,
+                                    )
+                                    .stroke(egui::Stroke::NONE),
+                                );
+                            }
+                        }
+                    }
                     plot_ui.line(egui_plot::Line::new(
                         "demand",
                         egui_plot::PlotPoints::from_ys_f64(&sources[source_id].demand),
@@ -317,7 +657,7 @@ fn main() {
                                 forcast.result.err[1..]
                                     .iter()
                                     .enumerate()
-                                    .map(|(i, &y)| [i as f64 + 1., y])
+                                    .map(|(i, &y)| [i as f64 + 1., if scaled_err { y / forcast.result.scale } else { y }])
                                     .collect::<egui_plot::PlotPoints>(),
                             ));
                         } else {
@@ -329,17 +669,95 @@ fn main() {
                                     .map(|(i, &y)| [i as f64 + 1., y])
                                     .collect::<egui_plot::PlotPoints>(),
                             ));
+                            if let Some(Ok(result)) = forcast.validation() {
+                                plot_ui.line(
+                                    egui_plot::Line::new(
+                                        format!("{} test", forcast.name),
+                                        result.test_forecast.clone(),
+                                    )
+                                    .style(egui_plot::LineStyle::dashed_loose()),
+                                );
+                            }
                         }
                     }
+                    if options.validation.enabled {
+                        let split = sources[source_id].demand.len().saturating_sub(options.validation.test_len);
+                        plot_ui.vline(egui_plot::VLine::new("test window", split as f64));
+                    }
                 });
 
+            if let Some((i, model)) = apply {
+                forecasts[i].model = model;
+                forecasts[i].update(ui.ctx(), &sources[source_id].demand, &options, i == band_method);
+            }
+
+            if changed {
+                export_job = None;
+                if source_changed {
+                    fits.iter_mut().for_each(|x| *x = None);
+                    period = seasonality::detect(&sources[source_id].demand);
+                    if let Some(period) = period {
+                        decomposition_period = period.len;
+                        for m in forecasts.iter_mut() {
+                            model::set_season_len(m.model.as_mut(), period.len);
+                        }
+                    }
+                }
+                for (i, m) in forecasts.iter_mut().enumerate() {
+                    m.update(ui.ctx(), &sources[source_id].demand, &options, i == band_method);
+                }
+            }
+
+            if save {
+                ctx.save(&Settings {
+                    sources: sources[builtin_sources..].to_vec(),
+                });
+            }
         });
     });
 }
 
+/// Runs `f` on a worker and repaints when it is done, fits of the arima methods take seconds.
+pub fn spawn<T: Send + 'static>(ctx: &egui::Context, f: impl FnOnce() -> T + Send + 'static) -> poll_promise::Promise<T> {
+    let (sender, promise) = poll_promise::Promise::new();
+    let ctx = ctx.clone();
+    common::execute(async move {
+        sender.send(f());
+        ctx.request_repaint();
+    });
+    promise
+}
+
+/// What is computed for every method besides the forecast itself.
+pub struct Options {
+    pub extra_periods: usize,
+    pub validation: validation::ValidationSettings,
+    pub intervals: intervals::IntervalSettings,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            extra_periods: 10,
+            validation: Default::default(),
+            intervals: Default::default(),
+        }
+    }
+}
+
+/// Persisted between sessions, the builtin sources are not part of it.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Settings {
+    pub sources: Vec<DemandSource>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct DemandSource {
     pub name: String,
     pub demand: Vec<f64>,
+    /// ISO dates of the periods, empty for series imported without a date column.
+    #[serde(default)]
+    pub dates: Vec<String>,
 }
 
 impl DemandSource {
@@ -347,6 +765,7 @@ impl DemandSource {
         Self {
             name: name.into(),
             demand,
+            dates: vec![],
         }
     }
 }
@@ -354,175 +773,82 @@ impl DemandSource {
 pub struct ForecastMethod {
     pub result: ForecastResult,
     pub name: &'static str,
-    pub method: &'static dyn Fn(&Vec<f64>, &ForecastParameters) -> Vec<f64>,
+    pub model: Box<dyn ForecastModel>,
+    /// Why the model could not be fitted, e.g. a series too short for it.
+    pub error: Option<String>,
+    /// Rolling origin validation refits many times, it runs on a worker.
+    pub validation: Option<poll_promise::Promise<Result<validation::Validation, String>>>,
+    /// Only for the method whose bands are shown, the residual refits take seconds for arima.
+    pub intervals: Option<poll_promise::Promise<intervals::Intervals>>,
 }
 
-pub struct ForecastResult {
-    pub forecast: Vec<f64>,
-    pub err: Vec<f64>,
-    pub bias_abs: f64,
-    pub bias_rel: f64,
-    pub mape: f64,
-    pub mae: f64,
-    pub mae_rel: f64,
-    pub rmse: f64,
-    pub rmse_rel: f64,
-}
-
-impl ForecastResult {
-    pub fn new(
-        method: &'static dyn Fn(&Vec<f64>, &ForecastParameters) -> Vec<f64>,
-        demand: &Vec<f64>,
-        parameters: &ForecastParameters,
-    ) -> Self {
-        let forecast = (method)(demand, parameters);
-        let mut err = vec![];
-        for i in 0..forecast.len().min(demand.len()) {
-            err.push(forecast[i] - demand[i]);
-        }
-        let results: Vec<_> = demand
-            .clone()
-            .into_iter()
-            .zip(err.clone())
-            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
-            .collect();
-
-        let demad: Vec<_> = results.iter().map(|x| x.0).collect();
-        let err: Vec<_> = results.iter().map(|x| x.1).collect();
-
-        let mape = results.iter().map(|x| x.1.abs() / x.0).sum::<f64>() / results.len() as f64;
-
-        let mae = results.iter().map(|x| x.1.abs()).sum::<f64>() / results.len() as f64;
-
-        let rmse = (results.iter().map(|x| x.1 * x.1).sum::<f64>() / results.len() as f64).sqrt();
+impl ForecastMethod {
+    pub fn new(name: &'static str, model: Box<dyn ForecastModel>, demand: &[f64], extra_periods: usize) -> Self {
+        let mut method = Self {
+            result: ForecastResult::new(vec![], demand),
+            name,
+            model,
+            error: None,
+            validation: None,
+            intervals: None,
+        };
+        method.forecast(demand, extra_periods);
+        method
+    }
 
-        let dem_ave = mean(&demad);
-        let bias_abs = mean(&err);
-        let bias_rel = bias_abs / dem_ave;
-        let mae_rel = mae / dem_ave;
-        let rmse_rel = rmse / dem_ave;
-        Self {
-            forecast,
-            err,
-            bias_rel,
-            bias_abs,
-            mape,
-            mae,
-            mae_rel,
-            rmse,
-            rmse_rel,
-        }
+    /// None while validation is off or still running.
+    pub fn validation(&self) -> Option<&Result<validation::Validation, String>> {
+        self.validation.as_ref().and_then(|x| x.ready())
     }
-}
 
-impl ForecastMethod {
-    pub fn new(
-        name: &'static str,
-        method: &'static dyn Fn(&Vec<f64>, &ForecastParameters) -> Vec<f64>,
-        demand: &Vec<f64>,
-        parameters: &ForecastParameters,
-    ) -> Self {
-        Self {
-            result: ForecastResult::new(method, demand, parameters),
-            name,
-            method,
+    pub fn update(&mut self, ctx: &egui::Context, demand: &[f64], options: &Options, bands: bool) {
+        self.forecast(demand, options.extra_periods);
+        self.validation = options.validation.enabled.then(|| {
+            let model = self.model.clone();
+            let demand = demand.to_vec();
+            let settings = options.validation.clone();
+            spawn(ctx, move || validation::validate(model.as_ref(), &demand, &settings))
+        });
+        if bands {
+            self.update_intervals(ctx, demand, options);
+        } else {
+            self.intervals = None;
         }
     }
 
-    pub fn update(&mut self, demand: &Vec<f64>, parameters: &ForecastParameters) {
-        self.result = ForecastResult::new(self.method, demand, parameters);
+    pub fn update_intervals(&mut self, ctx: &egui::Context, demand: &[f64], options: &Options) {
+        self.intervals = (options.intervals.enabled && self.error.is_none()).then(|| {
+            let model = self.model.clone();
+            let demand = demand.to_vec();
+            let forecast = self.result.forecast.clone();
+            let extra_periods = options.extra_periods;
+            let settings = options.intervals.clone();
+            spawn(ctx, move || {
+                intervals::intervals(model.as_ref(), &demand, &forecast, extra_periods, &settings)
+            })
+        });
     }
-}
-
-pub fn methods(demand: &Vec<f64>, parameters: &ForecastParameters) -> Vec<ForecastMethod> {
-    let mut methods = vec![];
-
-    methods.push(ForecastMethod::new(
-        "moving average",
-        &|demand, parameters| moving_average(demand, parameters.extra_periods, parameters.n),
-        demand,
-        parameters,
-    ));
-    methods.push(ForecastMethod::new(
-        "exp smooth",
-        &|demand, parameters| exp_smooth(demand, parameters.extra_periods, parameters.alpha),
-        demand,
-        parameters,
-    ));
-    methods.push(ForecastMethod::new(
-        "double exp smooth",
-        &|demand, parameters| {
-            double_exp_smooth(
-                demand,
-                parameters.extra_periods,
-                parameters.alpha,
-                parameters.beta,
-            )
-        },
-        demand,
-        parameters,
-    ));
-    methods.push(ForecastMethod::new(
-        "double exp smooth damped",
-        &|demand, parameters| {
-            double_exp_smooth_damped(
-                demand,
-                parameters.extra_periods,
-                parameters.alpha,
-                parameters.beta,
-                parameters.phi,
-            )
-        },
-        demand,
-        parameters,
-    ));
-    methods.push(ForecastMethod::new(
-        "triple exp smooth",
-        &|demand, parameters| {
-            triple_exp_smooth(
-                demand,
-                parameters.season_len,
-                parameters.extra_periods,
-                parameters.alpha,
-                parameters.beta,
-                parameters.phi,
-                parameters.gamma,
-            )
-        },
-        demand,
-        parameters,
-    ));
-
-    return methods;
-}
 
-pub struct ForecastParameters {
-    pub extra_periods: usize,
-    pub season_len: usize,
-    pub n: usize,
-    pub alpha: f64,
-    pub beta: f64,
-    pub phi: f64,
-    pub gamma: f64,
+    fn forecast(&mut self, demand: &[f64], extra_periods: usize) {
+        let forecast = match self.model.forecast(demand, extra_periods) {
+            Ok(forecast) => {
+                self.error = None;
+                forecast
+            }
+            Err(err) => {
+                self.error = Some(err);
+                vec![f64::NAN; demand.len() + extra_periods]
+            }
+        };
+        self.result = ForecastResult::new(forecast, demand);
+    }
 }
 
-impl Default for ForecastParameters {
-    fn default() -> Self {
-        Self {
-            extra_periods: 10,
-            n: 5,
-            season_len: 12,
-            alpha: 0.4,
-            beta: 0.4,
-            phi: 0.9,
-            gamma: 0.3,
-            // season_len: 4,
-            // alpha: 0.8,
-            // beta: 0.1,
-            // phi: 1.0,
-            // gamma: 0.4,
-        }
-    }
+pub fn methods(demand: &[f64], extra_periods: usize) -> Vec<ForecastMethod> {
+    models::defaults()
+        .into_iter()
+        .map(|(name, model)| ForecastMethod::new(name, model, demand, extra_periods))
+        .collect()
 }
 
 pub fn slider_u(ui: &mut Ui, value: &mut usize, text: &str) -> bool {
@@ -543,165 +869,3 @@ pub fn slider_f(ui: &mut Ui, value: &mut f64, text: &str) -> bool {
     }
     false
 }
-
-fn moving_average(demand: &[f64], extra_periods: usize, n: usize) -> Vec<f64> {
-    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
-
-    for i in n..demand.len() {
-        forecast[i] = mean(&demand[i - n..i]);
-    }
-
-    let avg = mean(&demand[demand.len() - n..]);
-    for i in demand.len()..demand.len() + extra_periods {
-        forecast[i] = avg;
-    }
-
-    forecast
-}
-
-fn lin_smooth(alpha: f64, a: f64, b: f64) -> f64 {
-    alpha * a + (1. - alpha) * b
-}
-
-fn exp_smooth(demand: &[f64], extra_periods: usize, alpha: f64) -> Vec<f64> {
-    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
-
-    forecast[1] = demand[0];
-
-    for i in 2..demand.len() + 1 {
-        forecast[i] = lin_smooth(alpha, demand[i - 1], forecast[i - 1]);
-    }
-
-    for i in demand.len() + 1..demand.len() + extra_periods {
-        forecast[i] = forecast[i - 1];
-    }
-
-    forecast
-}
-
-fn double_exp_smooth(demand: &[f64], extra_periods: usize, alpha: f64, beta: f64) -> Vec<f64> {
-    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
-
-    let mut a = demand[0];
-    let mut b = demand[1] - demand[0];
-
-    for i in 1..demand.len() {
-        forecast[i] = a + b;
-        let a_new = lin_smooth(alpha, demand[i], a + b);
-        b = lin_smooth(beta, a_new - a, b);
-        a = a_new;
-    }
-
-    for i in demand.len()..demand.len() + extra_periods {
-        forecast[i] = a + b;
-        a = forecast[i];
-        b = b;
-    }
-
-    forecast
-}
-
-fn double_exp_smooth_damped(
-    demand: &[f64],
-    extra_periods: usize,
-    alpha: f64,
-    beta: f64,
-    phi: f64,
-) -> Vec<f64> {
-    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
-
-    let mut a = demand[0];
-    let mut b = demand[1] - demand[0];
-
-    for i in 1..demand.len() {
-        forecast[i] = a + phi * b;
-        let a_new = lin_smooth(alpha, demand[i], a + phi * b);
-        b = lin_smooth(beta, a_new - a, phi * b);
-        a = a_new;
-    }
-
-    for i in demand.len()..demand.len() + extra_periods {
-        forecast[i] = a + phi * b;
-        a = forecast[i];
-        b = phi * b;
-    }
-
-    forecast
-}
-
-fn triple_exp_smooth(
-    demand: &[f64],
-    season_len: usize,
-    extra_periods: usize,
-    alpha: f64,
-    beta: f64,
-    phi: f64,
-    gamma: f64,
-) -> Vec<f64> {
-    let mut forecast = vec![f64::NAN; demand.len() + extra_periods];
-
-    let mut seasonal_factors = seasonal_factors(&demand, season_len);
-
-    let mut a = demand[0] / seasonal_factors[0];
-    let seasonal_factor1 = if seasonal_factors.len() > 1 {
-        seasonal_factors[1]
-    } else {
-        seasonal_factors[0]
-    };
-    let mut b = (demand[1] / seasonal_factor1) - (demand[0] / seasonal_factors[0]);
-
-    for i in 1..season_len {
-        forecast[i] = (a + phi * b) * seasonal_factors[i];
-        let a_new = lin_smooth(alpha, demand[i] / seasonal_factors[i], a + phi * b);
-        b = lin_smooth(beta, a_new - a, phi * b);
-        a = a_new;
-    }
-
-    for i in season_len..demand.len() {
-        let last_s = seasonal_factors[i % season_len];
-
-        forecast[i] = (a + phi * b) * last_s;
-        let a_new = lin_smooth(alpha, demand[i] / last_s, a + phi * b);
-        b = lin_smooth(beta, a_new - a, phi * b);
-        seasonal_factors[i % season_len] = lin_smooth(gamma, demand[i] / a_new, last_s);
-        a = a_new;
-    }
-
-    for i in demand.len()..demand.len() + extra_periods {
-        let last_s = seasonal_factors[i % season_len];
-
-        forecast[i] = (a + phi * b) * last_s;
-        a = forecast[i] / last_s;
-        b = phi * b;
-    }
-
-    forecast
-}
-
-fn mean(data: &[f64]) -> f64 {
-    let sum: f64 = data.iter().sum();
-    sum / data.len() as f64
-}
-
-fn seasonal_factors(d: &[f64], season_len: usize) -> Vec<f64> {
-    let mut seasonal_factors = vec![f64::NAN; season_len];
-
-    for i in 0..season_len {
-        let mut sum = 0.0;
-        let mut count = 0;
-
-        let mut j = i;
-        while j < d.len() {
-            sum += d[j];
-            count += 1;
-            j += season_len;
-        }
-
-        seasonal_factors[i] = if count > 0 { sum / count as f64 } else { 0.0 };
-    }
-    let mean = mean(&seasonal_factors);
-    for i in 0..season_len {
-        seasonal_factors[i] /= mean;
-    }
-    return seasonal_factors;
-}
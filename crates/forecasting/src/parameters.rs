@@ -0,0 +1,62 @@
+use egui::Ui;
+use forecasting_engine::ForecastModel;
+use forecasting_engine::model::{Kind, MAX_ORDER, MAX_SEASON_LEN, SEASON_LENGTH};
+
+/// One control per parameter of the model, returns whether any changed.
+pub fn show(model: &mut dyn ForecastModel, ui: &mut Ui) -> bool {
+    let mut changed = false;
+    for (i, parameter) in model.parameters().iter().enumerate() {
+        let value = model.get(i);
+        match parameter.kind {
+            Kind::Integer if parameter.name == SEASON_LENGTH => {
+                let mut temp = value as usize;
+                if ui
+                    .add(egui::Slider::new(&mut temp, 1..=MAX_SEASON_LEN).text(parameter.name))
+                    .changed()
+                {
+                    model.set(i, temp as f64);
+                    changed = true;
+                }
+            }
+            Kind::Integer => {
+                let mut temp = value as usize;
+                if crate::slider_u(ui, &mut temp, parameter.name) {
+                    model.set(i, temp as f64);
+                    changed = true;
+                }
+            }
+            Kind::Unit => {
+                let mut temp = value;
+                if crate::slider_f(ui, &mut temp, parameter.name) {
+                    model.set(i, temp);
+                    changed = true;
+                }
+            }
+            Kind::Order => {
+                let mut temp = value as usize;
+                if ui
+                    .add(egui::Slider::new(&mut temp, 0..=MAX_ORDER).text(parameter.name))
+                    .changed()
+                {
+                    model.set(i, temp as f64);
+                    changed = true;
+                }
+            }
+            Kind::Choice(variants) => {
+                let mut temp = value as usize;
+                egui::ComboBox::from_label(parameter.name)
+                    .selected_text(parameter.format(value))
+                    .show_ui(ui, |ui| {
+                        for (index, variant) in variants.iter().enumerate() {
+                            ui.selectable_value(&mut temp, index, *variant);
+                        }
+                    });
+                if temp != value as usize {
+                    model.set(i, temp as f64);
+                    changed = true;
+                }
+            }
+        }
+    }
+    changed
+}